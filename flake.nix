@@ -42,7 +42,8 @@
         craneLib = crane.mkLib pkgs;
 
         markdownFilter = path: _type: builtins.match ".*md$" path != null;
-        markdownOrCargo = path: type: (markdownFilter path type) || (craneLib.filterCargoSources path type);
+        fixtureFilter = path: _type: builtins.match ".*(ics|eml|json)$" path != null;
+        markdownOrCargo = path: type: (markdownFilter path type) || (fixtureFilter path type) || (craneLib.filterCargoSources path type);
 
         src = lib.cleanSourceWith {
           src = craneLib.path ./.;
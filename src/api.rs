@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+use reqwest::{
+    blocking::{Client, Response},
+    StatusCode,
+};
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::config::ApiConfig;
+
+/// environment variable holding the api token. it takes precedence over the config
+/// and the keyring.
+pub const TOKEN_ENV: &str = "PROTOTOOL_API_TOKEN";
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// `path` cant be joined to the base url of the api
+    InvalidUrl {
+        path: String,
+        source: url::ParseError,
+    },
+    /// the request failed, even after retrying
+    Request {
+        endpoint: Url,
+        source: reqwest::Error,
+    },
+    /// the api answered with 401 or 403, so the token is missing or invalid
+    Unauthorized { endpoint: Url, status: StatusCode },
+    /// the api answered with any other unsuccessful status
+    Status { endpoint: Url, status: StatusCode },
+    /// the response doesnt match the expected type
+    Deserialize {
+        endpoint: Url,
+        source: reqwest::Error,
+    },
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUrl { path, .. } => write!(f, "invalid api path '{}'", path),
+            Self::Request { endpoint, .. } => write!(f, "request to '{}' failed", endpoint),
+            Self::Unauthorized { endpoint, status } => write!(
+                f,
+                "'{}' answered with {}, set a token via {} or the keyring",
+                endpoint, status, TOKEN_ENV
+            ),
+            Self::Status { endpoint, status } => {
+                write!(f, "'{}' answered with {}", endpoint, status)
+            }
+            Self::Deserialize { endpoint, .. } => {
+                write!(f, "unexpected response from '{}'", endpoint)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidUrl { source, .. } => Some(source),
+            Self::Request { source, .. } | Self::Deserialize { source, .. } => Some(source),
+            Self::Unauthorized { .. } | Self::Status { .. } => None,
+        }
+    }
+}
+
+/// client for the api of the website. requests time out, are retried with
+/// exponential backoff and carry the bearer token, if there is one.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    base_url: Url,
+    client: Client,
+    token: Option<String>,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl ApiClient {
+    pub fn new(base_url: Url, config: &ApiConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()
+            .context("unable to create http client")?;
+
+        let token = find_token(&base_url, config)?;
+
+        Ok(Self {
+            base_url,
+            client,
+            token,
+            retries: config.retries,
+            backoff: Duration::from_millis(config.backoff),
+        })
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// the underlying http client, e.g. for requests to the pad or webhooks
+    pub const fn http(&self) -> &Client {
+        &self.client
+    }
+
+    /// fetches `path` below the base url and deserializes the json response
+    pub fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, ApiError> {
+        let mut endpoint = self
+            .base_url
+            .join(path)
+            .map_err(|source| ApiError::InvalidUrl {
+                path: path.to_string(),
+                source,
+            })?;
+
+        if !query.is_empty() {
+            endpoint.query_pairs_mut().extend_pairs(query);
+        }
+
+        let response = self.get(&endpoint)?;
+
+        response
+            .json()
+            .map_err(|source| ApiError::Deserialize { endpoint, source })
+    }
+
+    fn get(&self, endpoint: &Url) -> Result<Response, ApiError> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.client.get(endpoint.clone());
+
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+
+            let result = request.send();
+
+            let is_transient = match &result {
+                Ok(response) => {
+                    response.status().is_server_error()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+
+            if is_transient && attempt < self.retries {
+                thread::sleep(self.backoff.saturating_mul(2_u32.saturating_pow(attempt)));
+                attempt += 1;
+                continue;
+            }
+
+            let response = result.map_err(|source| ApiError::Request {
+                endpoint: endpoint.clone(),
+                source,
+            })?;
+
+            return match response.status() {
+                status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                    Err(ApiError::Unauthorized {
+                        endpoint: endpoint.clone(),
+                        status,
+                    })
+                }
+                status if !status.is_success() => Err(ApiError::Status {
+                    endpoint: endpoint.clone(),
+                    status,
+                }),
+                _ => Ok(response),
+            };
+        }
+    }
+}
+
+/// the token is taken from the environment, the config or the keyring, in this order
+fn find_token(base_url: &Url, config: &ApiConfig) -> Result<Option<String>> {
+    if let Some(token) = env::var(TOKEN_ENV).ok().filter(|t| !t.is_empty()) {
+        return Ok(Some(token));
+    }
+
+    if let Some(token) = &config.token {
+        return Ok(Some(token.clone()));
+    }
+
+    let keyring = match &config.keyring {
+        Some(path) => path.clone(),
+        None => match default_keyring() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(None),
+        },
+    };
+
+    read_keyring(&keyring, base_url)
+}
+
+/// '$XDG_CONFIG_HOME/prototool/keyring.toml' or '~/.config/prototool/keyring.toml'
+fn default_keyring() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("prototool/keyring.toml"))
+}
+
+/// looks up the token for the host of `base_url` in the keyring at `path`
+fn read_keyring(path: &Path, base_url: &Url) -> Result<Option<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("unable to read keyring '{}'", path.to_string_lossy()))?;
+
+    let tokens: HashMap<String, String> = toml::from_str(&content)
+        .with_context(|| format!("unable to parse keyring '{}'", path.to_string_lossy()))?;
+
+    Ok(base_url
+        .host_str()
+        .and_then(|host| tokens.get(host).cloned()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use tempfile::tempdir;
+    use url::Url;
+
+    use super::{ApiClient, ApiError};
+    use crate::{config::ApiConfig, test_server::stub_responses};
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Person {
+        name: String,
+    }
+
+    fn client(url: Url, token: Option<&str>) -> ApiClient {
+        let config = ApiConfig {
+            backoff: 1,
+            token: token.map(str::to_string),
+            ..Default::default()
+        };
+
+        ApiClient::new(url, &config).unwrap()
+    }
+
+    #[test]
+    fn get_json() {
+        let (url, handle) = stub_responses(&[("200 OK", r#"[{"name": "Valentin"}]"#)]);
+
+        let result: Vec<Person> = client(url, Some("geheim"))
+            .get_json("api/persons/by-role/", &[("role", "Rat")])
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+
+        assert_eq!(
+            result,
+            vec![Person {
+                name: "Valentin".to_string()
+            }]
+        );
+        assert_eq!(
+            requests[0].request_line,
+            "GET /api/persons/by-role/?role=Rat HTTP/1.1"
+        );
+        assert_eq!(requests[0].header("authorization"), Some("Bearer geheim"));
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let (url, handle) = stub_responses(&[
+            ("503 Service Unavailable", ""),
+            ("502 Bad Gateway", ""),
+            ("200 OK", "[]"),
+        ]);
+
+        let result: Vec<Person> = client(url, None).get_json("api/persons/", &[]).unwrap();
+
+        let requests = handle.join().unwrap();
+
+        assert!(result.is_empty());
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("authorization"), None);
+    }
+
+    #[test]
+    fn gives_up_after_retries() {
+        let (url, handle) = stub_responses(&[("500 Internal Server Error", ""); 4]);
+
+        let result = client(url, None).get_json::<Vec<Person>>("api/persons/", &[]);
+
+        handle.join().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ApiError::Status {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unauthorized() {
+        let (url, handle) = stub_responses(&[("401 Unauthorized", "")]);
+
+        let result = client(url, None).get_json::<Vec<Person>>("api/persons/", &[]);
+
+        handle.join().unwrap();
+
+        assert!(matches!(result, Err(ApiError::Unauthorized { .. })));
+    }
+
+    #[test]
+    fn read_keyring() {
+        let tmpdir = tempdir().unwrap();
+        let keyring = tmpdir.path().join("keyring.toml");
+
+        fs::write(&keyring, "\"fscs.hhu.de\" = \"geheim\"\n").unwrap();
+
+        let token = |url: &str| super::read_keyring(&keyring, &Url::parse(url).unwrap()).unwrap();
+
+        assert_eq!(token("https://fscs.hhu.de/"), Some("geheim".to_string()));
+        assert_eq!(token("http://localhost:8080/"), None);
+    }
+}
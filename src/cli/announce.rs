@@ -0,0 +1,171 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use url::Url;
+
+use prototool::{
+    api::ApiClient,
+    config::{self, Config},
+    einladung, post,
+    protokoll::sitzung,
+    timezone, webhook, Sitzung,
+};
+
+use super::{commit::CommitArgs, Runnable};
+
+/// Announce the next Sitzung with its Tagesordnung. Without any target, the
+/// announcement is printed
+#[derive(Debug, Args)]
+pub struct AnnounceCommand {
+    /// Endpoint to fetch the Sitzung from
+    #[arg(short = 'U', long, default_value = "https://fscs.hhu.de/")]
+    pub endpoint_url: Url,
+    /// Create a post on the website
+    #[arg(long)]
+    pub post: bool,
+    /// Under which language the post should be created
+    #[arg(short, long, default_value = "de")]
+    pub lang: String,
+    /// Force creation of the post, even if it already exists
+    #[arg(long, short)]
+    pub force: bool,
+    /// Write the mail including its headers to this file, '-' for stdout
+    #[arg(long, value_name = "FILE")]
+    pub mail: Option<PathBuf>,
+    /// Send the mail with sendmail
+    #[arg(long)]
+    pub sendmail: bool,
+    /// Post the announcement to a chat webhook
+    #[arg(long)]
+    pub chat: bool,
+    /// Webhook to post the announcement to [default: from prototool.toml]
+    #[arg(long)]
+    pub webhook_url: Option<Url>,
+    #[command(flatten)]
+    pub commit: CommitArgs,
+}
+
+impl Runnable for AnnounceCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let api = ApiClient::new(self.endpoint_url.clone(), &config.api)?;
+
+        let tz = &config.timezone;
+        let today = timezone::start_of_day(tz, timezone::today(tz));
+
+        println!("fetching sitzung...");
+        let mut sitzung = sitzung::fetch_sitzung(&api, today)?;
+        sitzung.to_timezone(tz);
+        sitzung.fill_antragsfrist(config.antraege.antragsfrist_days, tz);
+
+        if self.post {
+            self.commit.check(&cwd, &config, &post_branch(&sitzung))?;
+        }
+
+        let has_target = self.post || self.mail.is_some() || self.sendmail || self.chat;
+
+        if !has_target {
+            println!("{}", einladung::render_chat(&sitzung)?);
+            return Ok(());
+        }
+
+        if self.post {
+            self.create_post(&cwd, &config, &sitzung)?;
+        }
+
+        if self.mail.is_some() || self.sendmail {
+            self.send_mail(&config, &sitzung)?;
+        }
+
+        if self.chat {
+            let webhook_url = self
+                .webhook_url
+                .as_ref()
+                .or(config.announce.webhook_url.as_ref())
+                .ok_or_else(|| anyhow!("no webhook url given or configured"))?;
+
+            webhook::post_message(api.http(), webhook_url, &einladung::render_chat(&sitzung)?)?;
+
+            println!("posted einladung to webhook");
+        }
+
+        Ok(())
+    }
+}
+
+impl AnnounceCommand {
+    fn create_post(
+        &self,
+        root: &std::path::Path,
+        config: &Config,
+        sitzung: &Sitzung,
+    ) -> Result<()> {
+        let content = einladung::render_post(sitzung, &timezone::now(&config.timezone))?;
+        let path = sitzung
+            .datetime
+            .format("posts/%Y-%m-%d-einladung.md")
+            .to_string();
+
+        let post_path = post::create_post(&content, root, &self.lang, &path, self.force)?;
+
+        println!("created einladung at '{}'", post_path.to_string_lossy());
+
+        let message = format!("add einladung for {}", sitzung.datetime.format("%Y-%m-%d"));
+
+        self.commit
+            .commit(root, config, &post_path, &post_branch(sitzung), &message)
+    }
+
+    fn send_mail(&self, config: &Config, sitzung: &Sitzung) -> Result<()> {
+        let (Some(from), Some(to)) = (&config.announce.from, &config.announce.to) else {
+            bail!("'from' and 'to' need to be set in the 'announce' section of prototool.toml");
+        };
+
+        let mail = einladung::render_mail(sitzung, from, to, &timezone::now(&config.timezone))?;
+
+        match self.mail.as_deref() {
+            Some(path) if path.as_os_str() == "-" => print!("{}", mail),
+            Some(path) => {
+                fs::write(path, &mail).context("unable to write mail")?;
+                println!("wrote mail to '{}'", path.to_string_lossy());
+            }
+            None => (),
+        }
+
+        if self.sendmail {
+            let mut child = Command::new("sendmail")
+                .arg("-t")
+                .stdin(Stdio::piped())
+                .spawn()
+                .context("unable to spawn sendmail")?;
+
+            child
+                .stdin
+                .take()
+                .ok_or_else(|| anyhow!("unable to write to sendmail"))?
+                .write_all(mail.as_bytes())
+                .context("unable to write to sendmail")?;
+
+            let status = child.wait().context("unable to wait for sendmail")?;
+
+            if !status.success() {
+                bail!("sendmail failed with {}", status);
+            }
+
+            println!("sent mail to {}", to);
+        }
+
+        Ok(())
+    }
+}
+
+/// the branch the einladung for `sitzung` is committed on
+fn post_branch(sitzung: &Sitzung) -> String {
+    format!("einladung/{}", sitzung.datetime.format("%Y-%m-%d"))
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use prototool::{config::Config, git};
+
+/// Arguments controlling whether newly created files are committed
+#[derive(Debug, Args)]
+pub struct CommitArgs {
+    /// Commit the newly created file [default: from prototool.toml]
+    #[arg(long, overrides_with = "no_commit")]
+    pub commit: bool,
+    /// Dont commit the newly created file, even if enabled in prototool.toml
+    #[arg(long)]
+    pub no_commit: bool,
+    /// Create a new branch for the commit. Implies --commit
+    #[arg(long)]
+    pub branch: bool,
+}
+
+impl CommitArgs {
+    pub const fn should_branch(&self, config: &Config) -> bool {
+        !self.no_commit && (self.branch || config.git.branch)
+    }
+
+    pub const fn should_commit(&self, config: &Config) -> bool {
+        self.should_branch(config) || (!self.no_commit && (self.commit || config.git.commit))
+    }
+
+    /// fails if committing is enabled and the repository has unrelated staged changes,
+    /// or if `branch` should be created but already exists. this should be called
+    /// before creating any files.
+    pub fn check(&self, root: &Path, config: &Config, branch: &str) -> Result<()> {
+        if self.should_commit(config) {
+            git::ensure_nothing_staged(root)?;
+        }
+
+        if self.should_branch(config) {
+            git::ensure_branch_missing(root, branch)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn commit(
+        &self,
+        root: &Path,
+        config: &Config,
+        path: &Path,
+        branch: &str,
+        message: &str,
+    ) -> Result<()> {
+        if !self.should_commit(config) {
+            return Ok(());
+        }
+
+        if self.should_branch(config) {
+            git::create_branch(root, branch).context("unable to create branch")?;
+            println!("switched to new branch '{}'", branch);
+        }
+
+        git::commit_file(root, path, message).context("unable to commit")?;
+        println!("committed: {}", message);
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use anyhow::{Context, Result};
 use clap::Args;
 
-use prototool::post;
+use prototool::{config, post, timezone};
 
-use super::Runnable;
+use super::{commit::CommitArgs, prompt, Runnable};
 
 /// Create a new post.
 #[derive(Debug, Args)]
@@ -19,13 +19,24 @@ pub struct NewCommand {
     /// Force creation, even if a file already exist
     #[arg(long, short)]
     pub force: bool,
+    #[command(flatten)]
+    pub commit: CommitArgs,
 }
 
 impl Runnable for NewCommand {
     fn run(&self) -> Result<()> {
+        if self.edit {
+            prompt::ensure_interactive("--edit")?;
+        }
+
         let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let branch = format!("post/{}", self.path.trim_end_matches(".md"));
 
-        let now = chrono::Local::now().naive_local();
+        self.commit.check(&cwd, &config, &branch)?;
+
+        let now = timezone::now(&config.timezone).naive_local();
         let content = post::render_post_template(&now).context("error while rendering template")?;
 
         let post_path = post::create_post(
@@ -38,6 +49,11 @@ impl Runnable for NewCommand {
 
         println!("created new post at {}", post_path.to_string_lossy());
 
+        let message = format!("add post '{}'", self.path);
+
+        self.commit
+            .commit(&cwd, &config, &post_path, &branch, &message)?;
+
         if self.edit {
             post::edit(&post_path)?;
         }
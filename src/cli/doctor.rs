@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use arboard::Clipboard;
+use chrono::Local;
+use clap::Args;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use url::Url;
+
+use prototool::{
+    api::ApiClient,
+    config::{self, Config},
+    post,
+    protokoll::archive,
+    schema::{self, Shape},
+    Abmeldung, Event, Person, Sitzung,
+};
+
+use super::Runnable;
+
+/// Check the api, the website and the environment for problems
+#[derive(Debug, Args)]
+pub struct DoctorCommand {
+    /// Endpoint to check
+    #[arg(short = 'U', long, default_value = "https://fscs.hhu.de/")]
+    pub endpoint_url: Url,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&self, message: &str) {
+        println!("ok       {}", message);
+    }
+
+    fn warning(&mut self, message: &str) {
+        self.warnings += 1;
+        println!("warning  {}", message);
+    }
+
+    fn error(&mut self, message: &str) {
+        self.errors += 1;
+        println!("error    {}", message);
+    }
+}
+
+impl Runnable for DoctorCommand {
+    fn run(&self) -> Result<()> {
+        let mut report = Report::default();
+
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+
+        let config = match config::load_config(&cwd) {
+            Ok(config) => {
+                report.ok("prototool.toml");
+                config
+            }
+            Err(e) => {
+                report.error(&format!("{:#}", e));
+                Config::default()
+            }
+        };
+
+        check_website(&mut report, &cwd);
+        check_clipboard(&mut report);
+        self.check_api(&mut report, &config);
+
+        println!(
+            "\nfound {} errors and {} warnings",
+            report.errors, report.warnings
+        );
+
+        if report.errors > 0 {
+            bail!("prototool wont work as expected");
+        }
+
+        Ok(())
+    }
+}
+
+impl DoctorCommand {
+    fn check_api(&self, report: &mut Report, config: &Config) {
+        let api = match ApiClient::new(self.endpoint_url.clone(), &config.api) {
+            Ok(api) => api,
+            Err(e) => return report.error(&format!("{:#}", e)),
+        };
+
+        // the endpoints are only checked, if the website can be reached at all
+        match api.http().get(api.base_url().clone()).send() {
+            Ok(_) => report.ok(&format!("'{}' is reachable", api.base_url())),
+            Err(e) => {
+                return report.error(&format!(
+                    "'{}' isnt reachable: {:#}",
+                    api.base_url(),
+                    anyhow::Error::new(e)
+                ))
+            }
+        }
+
+        let timestamp = Local::now().fixed_offset().to_rfc3339();
+
+        let sitzungen = check_endpoint::<Sitzung>(
+            report,
+            &api,
+            "api/sitzungen/after/",
+            &[("timestamp", &timestamp), ("limit", "5")],
+            &schema::SITZUNG,
+        );
+
+        check_endpoint::<Person>(
+            report,
+            &api,
+            "api/persons/by-role/",
+            &[("role", "Rat")],
+            &schema::PERSON,
+        );
+
+        let sitzung_id = sitzungen
+            .as_ref()
+            .and_then(|s| s.first())
+            .and_then(|s| s["id"].as_str());
+
+        match sitzung_id {
+            Some(id) => {
+                check_endpoint::<Abmeldung>(
+                    report,
+                    &api,
+                    &format!("api/sitzungen/{}/abmeldungen/", id),
+                    &[],
+                    &schema::ABMELDUNG,
+                );
+            }
+            None => report.warning("no upcoming sitzung, so abmeldungen cant be checked"),
+        }
+
+        check_endpoint::<Event>(report, &api, "api/calendar/events/", &[], &schema::EVENT);
+    }
+}
+
+/// fetches `path` and checks each element of the response against `shape`. returns the
+/// elements, if the endpoint could be reached.
+fn check_endpoint<T: DeserializeOwned>(
+    report: &mut Report,
+    api: &ApiClient,
+    path: &str,
+    query: &[(&str, &str)],
+    shape: &'static Shape,
+) -> Option<Vec<Value>> {
+    let response: Value = match api.get_json(path, query) {
+        Ok(response) => response,
+        Err(e) => {
+            report.error(&format!("{}: {:#}", path, anyhow::Error::new(e)));
+            return None;
+        }
+    };
+
+    let mismatches = schema::check(&response, &Shape::List(shape));
+
+    for mismatch in &mismatches {
+        report.error(&format!("{}: {}", path, mismatch));
+    }
+
+    // the shapes might be out of sync with the types, so they are deserialized as well
+    if let Err(e) = serde_json::from_value::<Vec<T>>(response.clone()) {
+        if mismatches.is_empty() {
+            report.error(&format!("{}: {}", path, e));
+        }
+    } else if mismatches.is_empty() {
+        let count = response.as_array().map_or(0, Vec::len);
+        report.ok(&format!("{} ({} entries)", path, count));
+    }
+
+    response.as_array().cloned()
+}
+
+fn check_website(report: &mut Report, root: &Path) {
+    let content_dir = post::find_content_dir(root);
+
+    if !content_dir.exists() {
+        return report.error(&format!(
+            "content dir '{}' doesnt exist, is this the root of the website?",
+            content_dir.to_string_lossy()
+        ));
+    }
+
+    report.ok(&format!("content dir '{}'", content_dir.to_string_lossy()));
+
+    match archive::find_protokolle(root) {
+        Ok(protokolle) => report.ok(&format!("{} protokolle", protokolle.len())),
+        Err(e) => report.warning(&format!("{:#}", e)),
+    }
+}
+
+fn check_clipboard(report: &mut Report) {
+    match Clipboard::new() {
+        Ok(_) => report.ok("clipboard"),
+        Err(e) => report.warning(&format!(
+            "clipboard isnt available, so --to-clipboard and --from-clipboard wont work: {}",
+            e
+        )),
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use prototool::{
+    finanzen::{self, Budget},
+    protokoll::{archive, parse},
+};
+
+use super::Runnable;
+
+/// Summarize accepted Finanzanträge and compare them against the budget
+#[derive(Debug, Args)]
+pub struct FinanzenCommand {
+    /// Budget file, relative to the root of the website
+    #[arg(short, long, default_value = "haushalt.toml")]
+    pub budget: PathBuf,
+    /// Only show this semester, e.g. "SoSe 2022" or "WiSe 2022/23"
+    #[arg(short, long)]
+    pub semester: Option<String>,
+}
+
+impl Runnable for FinanzenCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+
+        let budget_path = cwd.join(&self.budget);
+        let budget = if budget_path.exists() {
+            finanzen::load_budget(&budget_path)?
+        } else {
+            eprintln!(
+                "warning: budget file '{}' doesnt exist",
+                budget_path.to_string_lossy()
+            );
+            Budget::new()
+        };
+
+        let mut protokolle = vec![];
+
+        for path in archive::find_protokolle(&cwd)? {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            match parse::parse(&content) {
+                Ok(protokoll) => protokolle.push(protokoll),
+                Err(e) => eprintln!("warning: skipping '{}': {}", path.to_string_lossy(), e),
+            }
+        }
+
+        let posten: Vec<_> = finanzen::summarize(&protokolle, &budget)
+            .into_iter()
+            .filter(|p| {
+                self.semester
+                    .as_ref()
+                    .is_none_or(|s| *s == p.semester.to_string())
+            })
+            .collect();
+
+        let mut current_semester = None;
+
+        for p in &posten {
+            let semester = p.semester.to_string();
+
+            if current_semester != Some(p.semester) {
+                println!("{}", semester);
+                current_semester = Some(p.semester);
+            }
+
+            match (p.budget, p.rest()) {
+                (Some(titel_budget), Some(rest)) => println!(
+                    "  {:<30} {:>14} von {:>14} (übrig: {})",
+                    p.haushaltstitel, p.ausgaben, titel_budget, rest
+                ),
+                _ => println!("  {:<30} {:>14}", p.haushaltstitel, p.ausgaben),
+            }
+        }
+
+        for p in posten.iter().filter(|p| p.is_overspent()) {
+            eprintln!(
+                "warning: '{}' is overspent in {} by {}",
+                p.haushaltstitel,
+                p.semester,
+                p.ausgaben - p.budget.unwrap_or_default()
+            );
+        }
+
+        Ok(())
+    }
+}
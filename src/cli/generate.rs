@@ -1,11 +1,13 @@
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
 
 use anyhow::{anyhow, Context, Result};
 use arboard::Clipboard;
 use askama::Template;
-use chrono::{DateTime, FixedOffset, Local, NaiveTime};
+use chrono::{DateTime, FixedOffset, NaiveDate};
 use clap::{ArgGroup, Args};
-use inquire::MultiSelect;
+use inquire::{MultiSelect, Select, Text};
 use reqwest::blocking::Client;
 use url::Url;
 
@@ -16,11 +18,17 @@ use libc::fork;
 #[cfg(target_os = "linux")]
 use rustix::stdio::{dup2_stdin, dup2_stdout};
 
-use super::Runnable;
+use super::{
+    commit::CommitArgs,
+    progress::{self, Progress},
+    prompt, Runnable,
+};
 use prototool::{
-    post,
-    protokoll::{self, events, person, sitzung},
-    Event, PersonWithAbmeldung, ProtokollTemplate, SitzungTyp,
+    api::ApiClient,
+    config::{self, Config},
+    pad, post,
+    protokoll::{self, archive, events, parse::ParsedProtokoll, person, sitzung},
+    timezone, Person, PersonWithAbmeldung, ProtokollTemplate, SitzungTyp, Top, TopTyp,
 };
 
 /// Generate a new Protokoll
@@ -58,28 +66,61 @@ pub struct GenerateCommand {
     /// Dont Ask for Presence
     #[arg(long)]
     pub no_ask_presence: bool,
+    /// File listing the present Räte, one name or id per line. Otherwise the Räte
+    /// present at the previous Sitzung are preselected
+    #[arg(long, value_name = "FILE")]
+    pub present_file: Option<PathBuf>,
+    /// A present guest, by name or id. Can be given multiple times
+    #[arg(long = "guest", value_name = "PERSON")]
+    pub guests: Vec<String>,
+    /// Who leads the Sitzung, by name or id
+    #[arg(long, value_name = "PERSON")]
+    pub redeleitung: Option<String>,
+    /// Who writes the Protokoll, by name or id
+    #[arg(long, value_name = "PERSON")]
+    pub protokoll: Option<String>,
+    /// Roles of persons, which are offered as guests
+    #[arg(
+        long,
+        value_name = "ROLE",
+        value_delimiter = ',',
+        default_values_t = ["Mitglied".to_string(), "Beratendes Mitglied".to_string()]
+    )]
+    pub guest_roles: Vec<String>,
+    /// Dont ask for changes to the Tagesordnung
+    #[arg(long)]
+    pub no_ask_tops: bool,
+    /// Only list events starting within this many days after the Sitzung
+    /// [default: from prototool.toml or 14]
+    #[arg(long, value_name = "DAYS")]
+    pub event_days: Option<u64>,
+    #[command(flatten)]
+    pub commit: CommitArgs,
 }
 
 impl Runnable for GenerateCommand {
     fn run(&self) -> Result<()> {
-        let client = Client::new();
+        if self.edit {
+            prompt::ensure_interactive("--edit")?;
+        }
+
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let api = ApiClient::new(self.endpoint_url.clone(), &config.api)?;
 
         if self.from_clipboard {
             return self.create_from_clipboard();
         } else if let Some(pad_url) = &self.from_pad {
-            return self.create_from_pad(&client, pad_url);
+            return self.create_from_pad(api.http(), pad_url);
         }
 
-        #[allow(clippy::unwrap_used)]
-        let now = chrono::Local::now()
-            .with_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
-            .unwrap()
-            .fixed_offset();
+        let today = timezone::start_of_day(&config.timezone, timezone::today(&config.timezone));
 
-        let template = self.build_template(&client, now)?;
+        let template = self.build_template(&api, &cwd, &config, today)?;
 
         // create_in_clipboard might fork, so we drop this here
-        drop(client);
+        drop(api);
 
         if self.to_clipboard {
             self.create_in_clipboard(template)
@@ -94,43 +135,244 @@ impl Runnable for GenerateCommand {
 impl GenerateCommand {
     fn build_template(
         &self,
-        client: &Client,
+        api: &ApiClient,
+        root: &Path,
+        config: &Config,
         sitzung_date: DateTime<FixedOffset>,
     ) -> Result<ProtokollTemplate> {
-        println!("fetching sitzung...");
-        let sitzung = sitzung::fetch_sitzung(&self.endpoint_url, client, sitzung_date)?;
+        let progress = Progress::new(&["sitzung", "räte", "events"]);
+
+        // räte and events dont depend on the sitzung, so they are fetched alongside it
+        let (sitzung_and_abmeldungen, all_raete, calendar_events) = thread::scope(|scope| {
+            let raete = scope.spawn(|| progress.track("räte", || person::fetch_raete(api)));
+            let calendar_events =
+                scope.spawn(|| progress.track("events", || events::fetch_calendar_events(api)));
+
+            let sitzung_and_abmeldungen = progress.track("sitzung", || {
+                let sitzung = sitzung::fetch_sitzung(api, sitzung_date)?;
+                let abmeldungen = person::fetch_abmeldungen(api, &sitzung)?;
+
+                Ok((sitzung, abmeldungen))
+            });
+
+            (
+                sitzung_and_abmeldungen,
+                progress::join(raete),
+                progress::join(calendar_events),
+            )
+        });
 
-        println!("fetching räte and withdrawals...");
-        let raete = person::fetch_raete(&self.endpoint_url, client)?;
-        let abmeldungen = person::fetch_abmeldungen(&self.endpoint_url, client, &sitzung)?;
-        let mut raete_and_abmeldung = person::determine_abgemeldet_räte(&raete, &abmeldungen);
+        let (sitzung, abmeldungen) = sitzung_and_abmeldungen?;
+        let raete = all_raete?;
 
-        if !self.no_ask_presence {
-            self.ask_present_räte(&mut raete_and_abmeldung)?;
-        }
+        let sitzung_day = timezone::convert(sitzung.datetime, &config.timezone).date_naive();
+        let previous = find_previous_protokoll(root, sitzung_day);
 
-        println!("fetching events...");
-        let events = events::fetch_calendar_events(&self.endpoint_url, client)?
-            .into_iter()
-            .map(|e| Event {
-                title: e.title,
-                location: e.location,
-                start: e.start.with_timezone::<Local>(&Local).into(),
-            })
-            .collect();
+        let mut event_filter = config.events.clone();
 
-        return Ok(ProtokollTemplate {
+        if let Some(days) = self.event_days {
+            event_filter.days = Some(days);
+        }
+
+        let mut template = ProtokollTemplate::assemble(
+            config,
             sitzung,
-            raete: raete_and_abmeldung,
-            events,
-        });
+            &raete,
+            abmeldungen,
+            calendar_events?,
+            &event_filter,
+            previous.as_ref(),
+        );
+
+        if !self.no_ask_tops && prompt::is_interactive() {
+            self.ask_tops(&mut template.sitzung.tops)?;
+        }
+
+        self.preselect_present_räte(previous.as_ref(), &mut template.raete)?;
+
+        if self.should_ask_presence() {
+            self.ask_present_räte(&mut template.raete)?;
+        }
+
+        (template.gaeste, template.redeleitung, template.protokoll) =
+            self.determine_participants(api, &template.raete)?;
+
+        Ok(template)
+    }
+
+    fn should_ask_presence(&self) -> bool {
+        !self.no_ask_presence && prompt::is_interactive()
+    }
+
+    /// determines the present guests and who leads the sitzung and writes the
+    /// protokoll, either from the given flags or by asking. both can be chosen from
+    /// the present räte and guests, and are left empty if not given.
+    fn determine_participants(
+        &self,
+        api: &ApiClient,
+        räte: &[PersonWithAbmeldung],
+    ) -> Result<(Vec<Person>, Option<Person>, Option<Person>)> {
+        let ask = self.should_ask_presence();
+
+        let candidates = if ask || !self.guests.is_empty() {
+            println!("fetching guests...");
+            person::fetch_guests(api, &self.guest_roles, räte)?
+        } else {
+            vec![]
+        };
+
+        let gaeste = if !self.guests.is_empty() {
+            self.guests
+                .iter()
+                .map(|entry| find_person(&candidates, entry, "guest"))
+                .collect::<Result<Vec<Person>>>()?
+        } else if ask && !candidates.is_empty() {
+            MultiSelect::new("select present guests:", candidates).prompt()?
+        } else {
+            vec![]
+        };
+
+        let presence_known = ask || self.present_file.is_some();
+        let participants = person::possible_participants(räte, &gaeste, presence_known);
+
+        let choose = |flag: Option<&String>, role: &str| match flag {
+            None if ask && participants.is_empty() => Ok(None),
+            None if ask => {
+                let message = format!("select {} (esc to skip):", role);
+
+                Ok(Select::new(&message, participants.clone()).prompt_skippable()?)
+            }
+            entry => person::find_participant(&participants, entry.map(String::as_str), role),
+        };
+
+        let redeleitung = choose(self.redeleitung.as_ref(), "redeleitung")?;
+        let protokoll = choose(self.protokoll.as_ref(), "protokoll")?;
+
+        Ok((gaeste, redeleitung, protokoll))
+    }
+
+    /// lets the writer reorder, remove or add normal tops. the weights of the normal
+    /// tops are reassigned to match the resulting order.
+    fn ask_tops(&self, tops: &mut Vec<Top>) -> Result<()> {
+        const DONE: &str = "done";
+        const MOVE: &str = "move top";
+        const REMOVE: &str = "remove top";
+        const ADD: &str = "add top";
+
+        let (mut normal, other): (Vec<Top>, Vec<Top>) =
+            tops.drain(..).partition(|t| t.typ == TopTyp::Normal);
+
+        normal.sort_by_key(|t| t.weight);
+
+        loop {
+            println!("tagesordnung:");
+            for (index, top) in normal.iter().enumerate() {
+                println!("  Top {}: {}", index + 2, top);
+            }
+
+            let action = Select::new("edit tops:", vec![DONE, MOVE, REMOVE, ADD]).prompt()?;
+
+            match action {
+                MOVE if !normal.is_empty() => {
+                    let top = Select::new("which top?", normal.clone()).raw_prompt()?;
+                    let positions = (2..normal.len() + 2).collect();
+                    let position = Select::new("move to:", positions).raw_prompt()?;
+
+                    let moved = normal.remove(top.index);
+                    normal.insert(position.index, moved);
+                }
+                REMOVE if !normal.is_empty() => {
+                    let top = Select::new("which top?", normal.clone()).raw_prompt()?;
+
+                    normal.remove(top.index);
+                }
+                ADD => {
+                    let name = Text::new("name:").prompt()?;
+
+                    normal.push(Top {
+                        weight: 0,
+                        name,
+                        antraege: vec![],
+                        typ: TopTyp::Normal,
+                        inhalt: String::new(),
+                    });
+                }
+                DONE => break,
+                _ => continue,
+            }
+        }
+
+        for (index, top) in normal.iter_mut().enumerate() {
+            top.weight = index as i64;
+        }
+
+        tops.extend(normal);
+        tops.extend(other);
+
+        Ok(())
+    }
+
+    /// marks the räte listed in the present file as present. without one, the räte
+    /// present at the previous sitzung are marked, unless they are abgemeldet.
+    fn preselect_present_räte(
+        &self,
+        previous: Option<&ParsedProtokoll>,
+        räte: &mut [PersonWithAbmeldung],
+    ) -> Result<()> {
+        if let Some(path) = &self.present_file {
+            let content = fs::read_to_string(path).with_context(|| {
+                format!("unable to read present file '{}'", path.to_string_lossy())
+            })?;
+
+            let unknown = person::mark_present(räte, &person::parse_present_list(&content));
+
+            for entry in unknown {
+                eprintln!("warning: '{}' from the present file isnt a rat", entry);
+            }
+
+            return Ok(());
+        }
+
+        if self.no_ask_presence {
+            return Ok(());
+        }
+
+        if let Some(protokoll) = previous {
+            person::mark_present_again(räte, &protokoll.anwesende);
+        }
+
+        Ok(())
     }
 
+    /// räte which are already marked as present or which are only abgemeldet for a
+    /// part of the sitzung are preselected, as they will most likely attend it
     fn ask_present_räte(&self, räte: &mut [PersonWithAbmeldung]) -> Result<()> {
-        let selected = MultiSelect::new("select present räte:", räte.to_vec()).prompt()?;
+        let options: Vec<String> = räte
+            .iter()
+            .map(|rat| match rat.abmeldung.as_ref() {
+                Some(abmeldung) if abmeldung.is_partial() => format!(
+                    "{} (entschuldigt {})",
+                    rat,
+                    abmeldung.details().unwrap_or_default()
+                ),
+                Some(_) => format!("{} (entschuldigt)", rat),
+                None => rat.to_string(),
+            })
+            .collect();
+
+        let preselected: Vec<usize> = räte
+            .iter()
+            .enumerate()
+            .filter(|(_, rat)| rat.anwesend || rat.is_partially_abgemeldet())
+            .map(|(index, _)| index)
+            .collect();
 
-        for rat in räte {
-            rat.anwesend = selected.iter().any(|s| s.id == rat.id);
+        let selected = MultiSelect::new("select present räte:", options)
+            .with_default(&preselected)
+            .raw_prompt()?;
+
+        for (index, rat) in räte.iter_mut().enumerate() {
+            rat.anwesend = selected.iter().any(|s| s.index == index);
         }
 
         Ok(())
@@ -138,23 +380,19 @@ impl GenerateCommand {
 
     fn write_to_file(&self, content: &str) -> Result<()> {
         let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
 
-        let markdown_opts = markdown::ParseOptions {
-            constructs: markdown::Constructs {
-                frontmatter: true,
-                ..markdown::Constructs::gfm()
-            },
-            ..markdown::ParseOptions::default()
-        };
-
-        let mdast = markdown::to_mdast(content, &markdown_opts)
-            .map_err(|_| anyhow!("unable to parse pad contents"))?;
+        let mdast = protokoll::parse_protokoll(content).context("unable to parse pad contents")?;
 
-        let frontmatter = protokoll::find_frontmatter(&mdast).unwrap();
+        let frontmatter = protokoll::find_frontmatter(&mdast)?;
 
         let timestamp = protokoll::find_protokoll_date(&frontmatter)
             .context("unable to determine protokoll date")?;
 
+        let branch = format!("protokoll/{}", timestamp.format("%Y-%m-%d"));
+
+        self.commit.check(&cwd, &config, &branch)?;
+
         let sitzung_kind = frontmatter.sitzung_kind.unwrap_or(SitzungTyp::Normal);
 
         let prefix = match sitzung_kind {
@@ -174,6 +412,14 @@ impl GenerateCommand {
 
         println!("created protokoll at '{}'", file_path.to_string_lossy());
 
+        let message = frontmatter.title.as_ref().map_or_else(
+            || format!("add protokoll from {}", timestamp.format("%d.%m.%Y")),
+            |title| format!("add {}", title),
+        );
+
+        self.commit
+            .commit(&cwd, &config, &file_path, &branch, &message)?;
+
         if self.edit {
             post::edit(&file_path)?
         }
@@ -239,11 +485,11 @@ impl GenerateCommand {
     }
 
     fn create_in_pad(&self, template: ProtokollTemplate) -> Result<()> {
-        let pad_url = template
-            .sitzung
-            .datetime
-            .format("https://pad.hhu.de/%Y-%m-%d-FSR-Informatik")
-            .to_string();
+        let pad_url = format!(
+            "{}{}",
+            pad::DEFAULT_PAD_URL,
+            pad::note_alias(&template.sitzung.datetime)
+        );
 
         println!("opening '{}'", pad_url);
 
@@ -281,3 +527,20 @@ impl GenerateCommand {
         self.write_to_file(content.as_str())
     }
 }
+
+fn find_person(persons: &[Person], entry: &str, kind: &str) -> Result<Person> {
+    persons
+        .iter()
+        .find(|p| p.matches(entry))
+        .cloned()
+        .ok_or_else(|| anyhow!("'{}' isnt a known {}", entry, kind))
+}
+
+/// the latest protokoll before `sitzung_day`, from which the present räte and the open
+/// todos are taken over
+pub fn find_previous_protokoll(root: &Path, sitzung_day: NaiveDate) -> Option<ParsedProtokoll> {
+    archive::find_previous_protokoll(root, sitzung_day).unwrap_or_else(|e| {
+        eprintln!("warning: unable to read previous protokoll: {}", e);
+        None
+    })
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use url::Url;
+
+use prototool::{
+    api::ApiClient,
+    config, ical,
+    protokoll::{events, sitzung},
+    timezone,
+};
+
+use super::{
+    progress::{self, Progress},
+    Runnable,
+};
+
+/// Export upcoming Sitzungen and events as an iCalendar file
+#[derive(Debug, Args)]
+pub struct IcalCommand {
+    /// Endpoint to fetch Sitzungen and events from
+    #[arg(short = 'U', long, default_value = "https://fscs.hhu.de/")]
+    pub endpoint_url: Url,
+    /// Where to write the calendar to
+    #[arg(short, long, default_value = "static/sitzungen.ics")]
+    pub output: PathBuf,
+    /// How many upcoming Sitzungen to include
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+impl Runnable for IcalCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let today = timezone::start_of_day(&config.timezone, timezone::today(&config.timezone));
+
+        let api = ApiClient::new(self.endpoint_url.clone(), &config.api)?;
+
+        let progress = Progress::new(&["sitzungen", "events"]);
+
+        let (upcoming_sitzungen, calendar_events) = thread::scope(|scope| {
+            let sitzungen = scope.spawn(|| {
+                progress.track("sitzungen", || {
+                    sitzung::fetch_sitzungen(&api, today, self.limit)
+                })
+            });
+
+            let events = progress.track("events", || events::fetch_calendar_events(&api));
+
+            (progress::join(sitzungen), events)
+        });
+
+        let mut sitzungen = upcoming_sitzungen?;
+        let events = calendar_events?;
+
+        for sitzung in &mut sitzungen {
+            sitzung.to_timezone(&config.timezone);
+            sitzung.fill_antragsfrist(config.antraege.antragsfrist_days, &config.timezone);
+        }
+
+        let calendar = ical::render_calendar(&sitzungen, &events, chrono::Utc::now());
+
+        if let Some(parent) = self.output.parent() {
+            fs::create_dir_all(parent).context("unable to create output directory")?;
+        }
+
+        fs::write(&self.output, calendar).context("unable to write calendar")?;
+
+        println!(
+            "exported {} sitzungen and {} events to '{}'",
+            sitzungen.len(),
+            events.len(),
+            self.output.to_string_lossy()
+        );
+
+        Ok(())
+    }
+}
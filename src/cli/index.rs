@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+
+use prototool::protokoll::{
+    archive,
+    index::{self, IndexEntry},
+};
+
+use super::Runnable;
+
+/// Generate the overview pages of the Protokoll archive for each year
+#[derive(Debug, Args)]
+pub struct IndexCommand {
+    /// For which language the overview pages should be generated
+    #[arg(short, long, default_value = "de")]
+    pub lang: String,
+    /// Only list the overview pages which would be written
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+    /// Overwrite overview pages, which werent generated by prototool
+    #[arg(long, short)]
+    pub force: bool,
+}
+
+impl Runnable for IndexCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+
+        let suffix = format!(".{}.md", self.lang);
+        let mut years: BTreeMap<PathBuf, Vec<IndexEntry>> = BTreeMap::new();
+
+        for path in archive::find_protokolle(&cwd)? {
+            if !path.to_string_lossy().ends_with(&suffix) {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            let entry = match IndexEntry::from_protokoll(&path, &self.lang, &content) {
+                Ok(Some(entry)) => entry,
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("warning: skipping '{}': {}", path.to_string_lossy(), e);
+                    continue;
+                }
+            };
+
+            if let Some(dir) = path.parent() {
+                years.entry(dir.to_path_buf()).or_default().push(entry);
+            }
+        }
+
+        let mut failed = 0;
+
+        for (dir, entries) in years {
+            let year = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let index_path = dir.join(format!("_index.{}.md", self.lang));
+            let path_str = index_path.to_string_lossy();
+
+            let existing = fs::read_to_string(&index_path).ok();
+
+            if existing.as_deref().is_some_and(|e| !index::is_generated(e)) && !self.force {
+                eprintln!(
+                    "'{}' wasnt generated by prototool, use --force to overwrite it",
+                    path_str
+                );
+                failed += 1;
+                continue;
+            }
+
+            let content = index::render_index(&year, entries)?;
+
+            if existing.as_deref() == Some(content.as_str()) {
+                continue;
+            }
+
+            if self.dry_run {
+                println!("would write '{}'", path_str);
+                continue;
+            }
+
+            fs::write(&index_path, content)
+                .with_context(|| format!("unable to write '{}'", path_str))?;
+
+            println!("wrote '{}'", path_str);
+        }
+
+        if failed > 0 {
+            bail!("{} overview pages could not be written", failed);
+        }
+
+        Ok(())
+    }
+}
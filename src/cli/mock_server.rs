@@ -0,0 +1,60 @@
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use clap::Args;
+
+use prototool::mock::MockBackend;
+
+use super::Runnable;
+
+/// Serve a fake of the website api, to try prototool or test it without a backend
+#[derive(Debug, Args)]
+pub struct MockServerCommand {
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    pub port: u16,
+    /// Directory with 'sitzungen.json', 'persons.json', 'abmeldungen.json' and
+    /// 'events.json' to serve instead of the builtin examples
+    #[arg(long, value_name = "DIR")]
+    pub fixtures: Option<PathBuf>,
+    /// Serve the dates as they are in the fixtures, instead of moving them to the
+    /// coming week
+    #[arg(long)]
+    pub no_shift: bool,
+}
+
+impl Runnable for MockServerCommand {
+    fn run(&self) -> Result<()> {
+        let mut backend = match &self.fixtures {
+            Some(dir) => MockBackend::load(dir)?,
+            None => MockBackend::builtin()?,
+        };
+
+        if !self.no_shift {
+            backend.shift_to(Local::now().fixed_offset());
+        }
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, self.port))
+            .with_context(|| format!("unable to listen on port {}", self.port))?;
+
+        println!(
+            "serving mock api on 'http://localhost:{0}/', use it with '-U http://localhost:{0}/'",
+            self.port
+        );
+
+        for stream in listener.incoming() {
+            let result = stream
+                .context("unable to accept connection")
+                .and_then(|s| backend.handle(s));
+
+            match result {
+                Ok((request_line, status)) => println!("{} -> {}", request_line, status),
+                Err(e) => eprintln!("warning: {:#}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::sync::{Mutex, PoisonError};
+use std::thread::ScopedJoinHandle;
+
+use anyhow::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Running,
+    Done,
+    Failed,
+}
+
+/// shows the state of resources fetched in parallel on a single line, which is redrawn
+/// whenever one of them is finished. without a terminal, a line is printed for each
+/// finished resource instead.
+#[derive(Debug)]
+pub struct Progress {
+    tasks: Mutex<Vec<(&'static str, State)>>,
+    live: bool,
+}
+
+impl Progress {
+    pub fn new(names: &[&'static str]) -> Self {
+        let progress = Self {
+            tasks: Mutex::new(names.iter().map(|name| (*name, State::Running)).collect()),
+            live: io::stderr().is_terminal(),
+        };
+
+        if progress.live {
+            progress.draw(&progress.lock());
+        }
+
+        progress
+    }
+
+    /// runs `fetch` and marks `name` as done or failed afterwards
+    pub fn track<T>(&self, name: &'static str, fetch: impl FnOnce() -> Result<T>) -> Result<T> {
+        let result = fetch();
+
+        self.update(
+            name,
+            if result.is_ok() {
+                State::Done
+            } else {
+                State::Failed
+            },
+        );
+
+        result
+    }
+
+    fn update(&self, name: &'static str, state: State) {
+        let mut tasks = self.lock();
+
+        if let Some(task) = tasks.iter_mut().find(|(n, _)| *n == name) {
+            task.1 = state;
+        }
+
+        // the lock is kept while drawing, so the line isnt drawn by two threads at once
+        if self.live {
+            self.draw(&tasks);
+            return;
+        }
+
+        drop(tasks);
+
+        if state == State::Done {
+            eprintln!("fetched {}", name);
+        } else {
+            eprintln!("failed to fetch {}", name);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(&'static str, State)>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn draw(&self, tasks: &[(&'static str, State)]) {
+        let line = tasks
+            .iter()
+            .map(|(name, state)| match state {
+                State::Running => format!("{} …", name),
+                State::Done => format!("{} ✓", name),
+                State::Failed => format!("{} ✗", name),
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        let finished = tasks.iter().all(|(_, state)| *state != State::Running);
+        let mut stderr = io::stderr().lock();
+
+        // the progress is purely informational, so failing to draw it is ignored
+        let _ = write!(stderr, "\r\x1b[Kfetching {}", line);
+
+        if finished {
+            let _ = writeln!(stderr);
+        }
+
+        let _ = stderr.flush();
+    }
+}
+
+/// waits for a fetch running in another thread. panics are passed on to the caller.
+pub fn join<T>(handle: ScopedJoinHandle<'_, Result<T>>) -> Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
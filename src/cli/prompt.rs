@@ -0,0 +1,27 @@
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{bail, Result};
+
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// prompts are disabled, if `--non-interactive` was passed or if there is no terminal
+/// to prompt on, e.g. when running from cron or in a ci job
+pub fn init(non_interactive: bool) {
+    let has_terminal = io::stdin().is_terminal() && io::stderr().is_terminal();
+
+    INTERACTIVE.store(!non_interactive && has_terminal, Ordering::Relaxed);
+}
+
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// fails, if `what` would need user interaction while prompts are disabled
+pub fn ensure_interactive(what: &str) -> Result<()> {
+    if !is_interactive() {
+        bail!("{} needs an interactive terminal", what);
+    }
+
+    Ok(())
+}
@@ -9,8 +9,21 @@ use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
 
+mod announce;
+mod commit;
 mod create;
+mod doctor;
+mod finanzen;
 mod generate;
+mod ical;
+mod index;
+mod mock_server;
+mod progress;
+mod prompt;
+mod publish;
+mod search;
+mod serve;
+mod todos;
 
 pub trait Runnable {
     fn run(&self) -> Result<()>;
@@ -21,14 +34,40 @@ pub trait Runnable {
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+    /// Never prompt, use defaults and the given flags instead. This is implied, if
+    /// there is no terminal
+    #[arg(long, short = 'y', visible_alias = "yes", global = true)]
+    pub non_interactive: bool,
 }
 
+// the command is only constructed once, so its size doesnt matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Command {
+    #[command(name = "announce")]
+    AnnounceCommand(announce::AnnounceCommand),
+    #[command(name = "doctor")]
+    DoctorCommand(doctor::DoctorCommand),
+    #[command(name = "finanzen")]
+    FinanzenCommand(finanzen::FinanzenCommand),
     #[command(name = "gen")]
     GenerateCommand(generate::GenerateCommand),
+    #[command(name = "mock-server")]
+    MockServerCommand(mock_server::MockServerCommand),
     #[command(name = "new")]
     NewCommand(create::NewCommand),
+    #[command(name = "ical")]
+    IcalCommand(ical::IcalCommand),
+    #[command(name = "index")]
+    IndexCommand(index::IndexCommand),
+    #[command(name = "publish")]
+    PublishCommand(publish::PublishCommand),
+    #[command(name = "search")]
+    SearchCommand(search::SearchCommand),
+    #[command(name = "serve")]
+    ServeCommand(serve::ServeCommand),
+    #[command(name = "todos")]
+    TodosCommand(todos::TodosCommand),
 }
 
 use std::process::ExitCode;
@@ -36,6 +75,8 @@ use std::process::ExitCode;
 fn main() -> ExitCode {
     let args = Cli::parse();
 
+    prompt::init(args.non_interactive);
+
     match run(args.command) {
         Ok(_) => ExitCode::SUCCESS,
         Err(e) => {
@@ -52,7 +93,17 @@ fn main() -> ExitCode {
 
 fn run(command: Command) -> Result<()> {
     match command {
+        Command::AnnounceCommand(x) => x.run(),
+        Command::DoctorCommand(x) => x.run(),
+        Command::FinanzenCommand(x) => x.run(),
         Command::GenerateCommand(x) => x.run(),
+        Command::MockServerCommand(x) => x.run(),
         Command::NewCommand(x) => x.run(),
+        Command::IcalCommand(x) => x.run(),
+        Command::IndexCommand(x) => x.run(),
+        Command::PublishCommand(x) => x.run(),
+        Command::SearchCommand(x) => x.run(),
+        Command::ServeCommand(x) => x.run(),
+        Command::TodosCommand(x) => x.run(),
     }
 }
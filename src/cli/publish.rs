@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{ArgGroup, Args};
+
+use prototool::{
+    config,
+    protokoll::{self, archive, publish},
+    timezone,
+};
+
+use super::Runnable;
+
+/// Publish a Protokoll
+#[derive(Debug, Args)]
+#[clap(group(
+            ArgGroup::new("target")
+                .required(true)
+                .args(&["paths", "due"]),
+        ))]
+pub struct PublishCommand {
+    /// Paths of the protokolle to publish
+    pub paths: Vec<PathBuf>,
+    /// Publish all drafts whose 'hiddenUntil' date has passed
+    #[arg(long)]
+    pub due: bool,
+    /// Only list the protokolle which would be published
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+    /// Publish even if the protokoll still contains placeholders
+    #[arg(long, short)]
+    pub force: bool,
+}
+
+impl Runnable for PublishCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let today = timezone::today(&config.timezone);
+
+        if !self.due {
+            for path in &self.paths {
+                self.publish(path, today)?;
+            }
+
+            return Ok(());
+        }
+
+        let mut failed = 0;
+
+        for path in archive::find_protokolle(&cwd)? {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            let frontmatter = match protokoll::parse_protokoll(&content)
+                .and_then(|mdast| protokoll::find_frontmatter(&mdast))
+            {
+                Ok(frontmatter) => frontmatter,
+                Err(e) => {
+                    eprintln!("warning: skipping '{}': {}", path.to_string_lossy(), e);
+                    continue;
+                }
+            };
+
+            if !publish::is_due(&frontmatter, today) {
+                continue;
+            }
+
+            if let Err(e) = self.publish(&path, today) {
+                eprintln!("{:#}", e);
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            bail!("{} protokolle could not be published", failed);
+        }
+
+        Ok(())
+    }
+}
+
+impl PublishCommand {
+    fn publish(&self, path: &Path, today: chrono::NaiveDate) -> Result<()> {
+        let path_str = path.to_string_lossy();
+
+        let content =
+            fs::read_to_string(path).with_context(|| format!("unable to read '{}'", path_str))?;
+
+        let placeholders = publish::find_placeholders(&content);
+
+        if !placeholders.is_empty() && !self.force {
+            let lines: Vec<String> = placeholders.iter().map(|p| format!("  {}", p)).collect();
+
+            bail!(
+                "'{}' still contains placeholders:\n{}",
+                path_str,
+                lines.join("\n")
+            );
+        }
+
+        if self.dry_run {
+            println!("would publish '{}'", path_str);
+            return Ok(());
+        }
+
+        let published = publish::publish(&content, today)
+            .with_context(|| format!("unable to publish '{}'", path_str))?;
+
+        fs::write(path, published).with_context(|| format!("unable to write '{}'", path_str))?;
+
+        println!("published '{}'", path_str);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::Args;
+
+use prototool::{
+    protokoll::{
+        archive,
+        search::{self, SearchCache, SearchQuery},
+    },
+    SitzungTyp,
+};
+
+use super::Runnable;
+
+/// Search the Tops and Anträge of all Protokolle
+#[derive(Debug, Args)]
+pub struct SearchCommand {
+    /// Words which all have to occur within a Top or Antrag, regardless of case
+    #[arg(required = true)]
+    pub query: Vec<String>,
+    /// Only search Protokolle from this date on, e.g. 2022-04-01
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+    /// Only search Protokolle of this kind, e.g. normal or vv
+    #[arg(long)]
+    pub kind: Option<SitzungTyp>,
+    /// Only show Tops and Anträge with an accepted vote
+    #[arg(long)]
+    pub beschluss_only: bool,
+    /// Where the index of the Protokolle is cached [default: ~/.cache/prototool/search.json]
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+}
+
+impl Runnable for SearchCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+
+        let cache_path = self.cache.clone().or_else(search::default_cache_path);
+        let mut cache = cache_path
+            .as_deref()
+            .map(SearchCache::load)
+            .unwrap_or_default();
+
+        let failed = cache.update(&archive::find_protokolle(&cwd)?)?;
+
+        for (path, e) in failed {
+            eprintln!("warning: skipping '{}': {}", path.to_string_lossy(), e);
+        }
+
+        // the cache only speeds up the next search, so the results are shown anyway
+        if let Some(path) = &cache_path {
+            if let Err(e) = cache.save(path) {
+                eprintln!("warning: {:#}", e);
+            }
+        }
+
+        let query = SearchQuery {
+            words: self.query.clone(),
+            since: self.since,
+            kind: self.kind.clone(),
+            beschluss_only: self.beschluss_only,
+        };
+
+        let hits = cache.search(&cwd, &query);
+
+        for hit in &hits {
+            let location = hit.section.antrag.as_ref().map_or_else(
+                || format!("Top: {}", hit.section.top),
+                |antrag| format!("Top: {} › Antrag: {}", hit.section.top, antrag),
+            );
+
+            println!(
+                "{} {} – {}",
+                hit.date.format("%d.%m.%Y"),
+                hit.sitzung_kind.bezeichnung(),
+                location
+            );
+            println!(
+                "  {}",
+                hit.path
+                    .strip_prefix(&cwd)
+                    .unwrap_or(hit.path)
+                    .to_string_lossy()
+            );
+            println!("  {}", hit.snippet);
+        }
+
+        eprintln!("{} results", hits.len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use askama::Template;
+use chrono::TimeDelta;
+use clap::Args;
+use url::Url;
+use uuid::Uuid;
+
+use prototool::{
+    api::ApiClient,
+    config::{self, Config},
+    pad::{self, NoteState},
+    protokoll::{events, person, sitzung},
+    timezone, ProtokollTemplate, Sitzung,
+};
+
+use super::{
+    generate,
+    progress::{self, Progress},
+    Runnable,
+};
+
+/// How many upcoming Sitzungen are looked at per poll
+const SITZUNG_LOOKAHEAD: usize = 5;
+
+/// Create the pad for each upcoming Sitzung shortly before it and announce it
+#[derive(Debug, Args)]
+pub struct ServeCommand {
+    /// Endpoint to fetch Sitzungen from
+    #[arg(short = 'U', long, default_value = "https://fscs.hhu.de/")]
+    pub endpoint_url: Url,
+    /// HedgeDoc instance to create the pads on [default: from prototool.toml or
+    /// https://pad.hhu.de/]
+    #[arg(long)]
+    pub pad_url: Option<Url>,
+    /// Webhook to post the links of new pads to [default: from prototool.toml]
+    #[arg(long)]
+    pub webhook_url: Option<Url>,
+    /// How many minutes before a Sitzung its pad is created [default: from
+    /// prototool.toml or 120]
+    #[arg(long, value_name = "MINUTES")]
+    pub lead_time: Option<u64>,
+    /// Only check for due pads once and exit, e.g. when run from cron
+    #[arg(long)]
+    pub once: bool,
+}
+
+impl Runnable for ServeCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let mut config = config::load_config(&cwd)?;
+
+        if let Some(pad_url) = &self.pad_url {
+            config.serve.pad_url = pad_url.clone();
+        }
+
+        if let Some(webhook_url) = &self.webhook_url {
+            config.serve.webhook_url = Some(webhook_url.clone());
+        }
+
+        if let Some(lead_time) = self.lead_time {
+            config.serve.lead_time = lead_time;
+        }
+
+        let api = ApiClient::new(self.endpoint_url.clone(), &config.api)?;
+        let mut prepared = HashMap::new();
+
+        if self.once {
+            return self.prepare_due_pads(&cwd, &api, &config, &mut prepared);
+        }
+
+        println!(
+            "creating pads {} minutes before each sitzung",
+            config.serve.lead_time
+        );
+
+        loop {
+            if let Err(e) = self.prepare_due_pads(&cwd, &api, &config, &mut prepared) {
+                eprintln!("warning: {:#}", e);
+            }
+
+            thread::sleep(Duration::from_secs(config.serve.poll_interval));
+        }
+    }
+}
+
+impl ServeCommand {
+    fn prepare_due_pads(
+        &self,
+        root: &Path,
+        api: &ApiClient,
+        config: &Config,
+        prepared: &mut HashMap<Uuid, NoteState>,
+    ) -> Result<()> {
+        let now = timezone::now(&config.timezone);
+        let lead_time = TimeDelta::minutes(config.serve.lead_time.try_into()?);
+
+        let sitzungen = sitzung::fetch_sitzungen(api, now, SITZUNG_LOOKAHEAD)?;
+
+        for sitzung in sitzungen {
+            let mut state = prepared.get(&sitzung.id).copied();
+
+            if state.is_some_and(NoteState::is_done) || sitzung.datetime - lead_time > now {
+                continue;
+            }
+
+            let id = sitzung.id;
+            let result = self.prepare_pad(root, api, config, sitzung, &mut state);
+
+            if let Some(new_state) = state {
+                prepared.insert(id, new_state);
+            }
+
+            // a failed sitzung is retried on the next poll, without holding up the others
+            if let Err(e) = result {
+                eprintln!("warning: {:#}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// creates the pad for `sitzung` and announces it. pads which already exist are
+    /// left alone and not announced again. if only the announcement failed, the next
+    /// call just retries it.
+    fn prepare_pad(
+        &self,
+        root: &Path,
+        api: &ApiClient,
+        config: &Config,
+        mut sitzung: Sitzung,
+        state: &mut Option<NoteState>,
+    ) -> Result<()> {
+        sitzung.to_timezone(&config.timezone);
+
+        let alias = pad::note_alias(&sitzung.datetime);
+        let note_url = pad::note_url(&config.serve.pad_url, &alias)?;
+        let message = format!(
+            "Das Pad für die Sitzung am {} ist bereit: {}",
+            sitzung.datetime.format("%d.%m.%Y um %H:%M Uhr"),
+            note_url
+        );
+
+        let was_created = state.is_some();
+        let result = pad::prepare_note(
+            api.http(),
+            &config.serve.pad_url,
+            &alias,
+            || render_pad(root, api, config, sitzung),
+            config
+                .serve
+                .webhook_url
+                .as_ref()
+                .map(|url| (url, message.as_str())),
+            state,
+        );
+
+        match *state {
+            Some(NoteState::Existed) => println!("pad '{}' already exists", note_url),
+            Some(_) if !was_created => println!("created pad '{}'", note_url),
+            _ => (),
+        }
+
+        result
+    }
+}
+
+/// renders the protokoll template for `sitzung`, which is already in the configured
+/// timezone
+fn render_pad(root: &Path, api: &ApiClient, config: &Config, sitzung: Sitzung) -> Result<String> {
+    let progress = Progress::new(&["räte", "abmeldungen", "events"]);
+
+    let (all_raete, all_abmeldungen, calendar_events) = thread::scope(|scope| {
+        let raete = scope.spawn(|| progress.track("räte", || person::fetch_raete(api)));
+        let abmeldungen = scope
+            .spawn(|| progress.track("abmeldungen", || person::fetch_abmeldungen(api, &sitzung)));
+
+        let events = progress.track("events", || events::fetch_calendar_events(api));
+
+        (progress::join(raete), progress::join(abmeldungen), events)
+    });
+
+    let previous = generate::find_previous_protokoll(root, sitzung.datetime.date_naive());
+
+    let mut template = ProtokollTemplate::assemble(
+        config,
+        sitzung,
+        &all_raete?,
+        all_abmeldungen?,
+        calendar_events?,
+        &config.events,
+        previous.as_ref(),
+    );
+
+    if let Some(protokoll) = &previous {
+        person::mark_present_again(&mut template.raete, &protokoll.anwesende);
+    }
+
+    template.render().context("error while rendering template")
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate};
+use clap::Args;
+use url::Url;
+
+use prototool::{
+    api::ApiClient,
+    config,
+    protokoll::{archive, parse, person},
+    timezone,
+    todos::{self, TodoGroup},
+};
+
+use super::Runnable;
+
+/// how far back protokolle are searched for todos, unless '--since' is given
+const DEFAULT_WEEKS: u64 = 12;
+
+/// List the open ToDos of recent Protokolle by assignee
+#[derive(Debug, Args)]
+pub struct TodosCommand {
+    /// Endpoint to fetch the Räte from, to match the mentioned names against
+    #[arg(short = 'U', long, default_value = "https://fscs.hhu.de/")]
+    pub endpoint_url: Url,
+    /// Only consider Protokolle from this date on [default: 12 weeks ago]
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+    /// Write the ToDos as json to this file, '-' for stdout
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+    /// Write the ToDos as a markdown page to this file, '-' for stdout
+    #[arg(long, value_name = "FILE")]
+    pub markdown: Option<PathBuf>,
+}
+
+impl Runnable for TodosCommand {
+    fn run(&self) -> Result<()> {
+        let cwd = std::env::current_dir().context("unable to determine working directory")?;
+        let config = config::load_config(&cwd)?;
+
+        let today = timezone::today(&config.timezone);
+        let since = self.since.unwrap_or_else(|| {
+            today
+                .checked_sub_days(Days::new(DEFAULT_WEEKS * 7))
+                .unwrap_or(NaiveDate::MIN)
+        });
+
+        let mut protokolle = vec![];
+
+        for path in archive::find_protokolle(&cwd)? {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            match parse::parse(&content) {
+                Ok(protokoll) if protokoll.date >= since => protokolle.push(protokoll),
+                Ok(_) => (),
+                Err(e) => eprintln!("warning: skipping '{}': {}", path.to_string_lossy(), e),
+            }
+        }
+
+        let open_todos = todos::open_todos(&protokolle, today);
+
+        // without the räte, the todos are still grouped by the names as written
+        let api = ApiClient::new(self.endpoint_url.clone(), &config.api)?;
+        let raete = person::fetch_raete(&api).unwrap_or_else(|e| {
+            eprintln!("warning: unable to fetch räte: {:#}", e);
+            vec![]
+        });
+
+        let groups = todos::group_by_assignee(&open_todos, &raete);
+
+        if let Some(path) = &self.json {
+            let json =
+                serde_json::to_string_pretty(&groups).context("unable to serialize todos")?;
+
+            write_output(path, &(json + "\n"), "json")?;
+        }
+
+        if let Some(path) = &self.markdown {
+            write_output(path, &todos::render_page(&groups, today)?, "markdown page")?;
+        }
+
+        if self.json.is_none() && self.markdown.is_none() {
+            print_groups(&groups);
+        }
+
+        Ok(())
+    }
+}
+
+fn write_output(path: &Path, content: &str, kind: &str) -> Result<()> {
+    if path.as_os_str() == "-" {
+        print!("{}", content);
+        return Ok(());
+    }
+
+    fs::write(path, content).with_context(|| format!("unable to write {}", kind))?;
+    println!("wrote {} to '{}'", kind, path.to_string_lossy());
+
+    Ok(())
+}
+
+fn print_groups(groups: &[TodoGroup]) {
+    for group in groups {
+        match &group.assignee {
+            Some(assignee) if group.is_rat => println!("{}", assignee),
+            Some(assignee) => println!("{} (kein Rat)", assignee),
+            None => println!("ohne Zuständigkeit"),
+        }
+
+        for todo in &group.todos {
+            println!(
+                "  - {} (seit {}, {} Tage)",
+                todo.text,
+                todo.since.format("%d.%m.%Y"),
+                todo.age
+            );
+        }
+    }
+}
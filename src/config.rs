@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use url::Url;
+
+use crate::{pad, protokoll::events::EventFilter, timezone};
+
+/// name of the config file, which is looked up in the root of the website
+pub const CONFIG_FILE: &str = "prototool.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// the timezone in which dates are queried and shown, e.g. 'Europe/Berlin'
+    pub timezone: Tz,
+    pub api: ApiConfig,
+    pub git: GitConfig,
+    pub events: EventFilter,
+    pub antraege: AntragConfig,
+    pub serve: ServeConfig,
+    pub announce: AnnounceConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            timezone: timezone::DEFAULT_TIMEZONE,
+            api: ApiConfig::default(),
+            git: GitConfig::default(),
+            events: EventFilter::default(),
+            antraege: AntragConfig::default(),
+            serve: ServeConfig::default(),
+            announce: AnnounceConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ApiConfig {
+    /// seconds after which a request to the api is aborted
+    pub timeout: u64,
+    /// how often failed requests are retried
+    pub retries: u32,
+    /// milliseconds to wait before the first retry. the wait is doubled for every
+    /// further retry.
+    pub backoff: u64,
+    /// bearer token for the api. as prototool.toml is usually committed, the
+    /// environment or the keyring should be preferred.
+    pub token: Option<String>,
+    /// toml file mapping api hosts to their tokens
+    pub keyring: Option<PathBuf>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 30,
+            retries: 3,
+            backoff: 500,
+            token: None,
+            keyring: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GitConfig {
+    /// commit newly created protokolle and posts
+    pub commit: bool,
+    /// create a new branch before committing
+    pub branch: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AntragConfig {
+    /// how many days before the sitzung the antragsfrist ends, if the api doesnt
+    /// provide one
+    pub antragsfrist_days: u64,
+}
+
+impl Default for AntragConfig {
+    fn default() -> Self {
+        Self {
+            antragsfrist_days: 3,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ServeConfig {
+    /// how many minutes before a sitzung its pad is created
+    pub lead_time: u64,
+    /// how many seconds to wait between looking for upcoming sitzungen
+    pub poll_interval: u64,
+    pub pad_url: Url,
+    /// where to announce newly created pads
+    pub webhook_url: Option<Url>,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        #[allow(clippy::unwrap_used)]
+        Self {
+            lead_time: 120,
+            poll_interval: 300,
+            pad_url: Url::parse(pad::DEFAULT_PAD_URL).unwrap(),
+            webhook_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AnnounceConfig {
+    /// sender of the einladung mail
+    pub from: Option<String>,
+    /// recipients of the einladung mail
+    pub to: Option<String>,
+    /// where to post the einladung as a chat message
+    pub webhook_url: Option<Url>,
+}
+
+/// loads the config from 'prototool.toml' in `root`. if there is no such file,
+/// the default config is used.
+pub fn load_config(root: &Path) -> Result<Config> {
+    let path = root.join(CONFIG_FILE);
+
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = fs::read_to_string(&path).context("unable to read config")?;
+
+    toml::from_str(content.as_str()).context("unable to parse config")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use tempfile::tempdir;
+
+    use std::fs;
+
+    #[test]
+    fn config_doesnt_exist() {
+        let tmpdir = tempdir().unwrap();
+
+        let config = super::load_config(tmpdir.path()).unwrap();
+
+        assert!(!config.git.commit);
+        assert!(!config.git.branch);
+        assert_eq!(config.antraege.antragsfrist_days, 3);
+        assert_eq!(config.timezone, chrono_tz::Europe::Berlin);
+    }
+
+    #[test]
+    fn load_config() {
+        let tmpdir = tempdir().unwrap();
+
+        fs::write(
+            tmpdir.path().join("prototool.toml"),
+            "timezone = \"America/Sao_Paulo\"\n\n[git]\ncommit = true\n\n[antraege]\nantragsfrist-days = 7\n",
+        )
+        .unwrap();
+
+        let config = super::load_config(tmpdir.path()).unwrap();
+
+        assert!(config.git.commit);
+        assert!(!config.git.branch);
+        assert_eq!(config.antraege.antragsfrist_days, 7);
+        assert_eq!(config.timezone, chrono_tz::America::Sao_Paulo);
+    }
+}
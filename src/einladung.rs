@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use askama::Template;
+use chrono::{DateTime, FixedOffset};
+
+use crate::{Sitzung, Top, TopTyp};
+
+/// a top as announced in the einladung, numbered the same way as in the protokoll
+#[derive(Debug, PartialEq, Eq)]
+pub struct Tagesordnungspunkt {
+    pub nummer: usize,
+    pub name: String,
+    pub antraege: Vec<String>,
+    /// the regularia, bericht or verschiedenes tops listed under a fixed punkt
+    pub unterpunkte: Vec<Unterpunkt>,
+}
+
+/// a top of the sitzung listed under one of the fixed tagesordnungspunkte
+#[derive(Debug, PartialEq, Eq)]
+pub struct Unterpunkt {
+    pub name: String,
+    pub antraege: Vec<String>,
+}
+
+fn tops_of_typ(sitzung: &Sitzung, typ: TopTyp) -> Vec<&Top> {
+    let mut result: Vec<&Top> = sitzung.tops.iter().filter(|t| t.typ == typ).collect();
+
+    result.sort_by_key(|t| t.weight);
+
+    result
+}
+
+fn antrag_titel(top: &Top) -> Vec<String> {
+    top.antraege.iter().map(|a| a.titel.clone()).collect()
+}
+
+/// the tops of `sitzung` in the order of the protokoll: regularia and berichte first,
+/// then the normal tops by weight and verschiedenes last.
+///
+/// Tops typed as regularia, bericht or verschiedenes are listed as unterpunkte of the
+/// matching fixed punkt, just like the protokoll template lists them.
+pub fn tagesordnung(sitzung: &Sitzung) -> Vec<Tagesordnungspunkt> {
+    let normal = tops_of_typ(sitzung, TopTyp::Normal);
+
+    let punkt = |nummer: usize, name: &str, typ: TopTyp| Tagesordnungspunkt {
+        nummer,
+        name: name.to_string(),
+        antraege: vec![],
+        unterpunkte: tops_of_typ(sitzung, typ)
+            .into_iter()
+            .map(|top| Unterpunkt {
+                name: top.name.clone(),
+                antraege: antrag_titel(top),
+            })
+            .collect(),
+    };
+
+    let mut result = vec![
+        punkt(0, "Regularia", TopTyp::Regularia),
+        punkt(1, "Berichte, Mail und Post", TopTyp::Bericht),
+    ];
+
+    for (index, top) in normal.iter().enumerate() {
+        result.push(Tagesordnungspunkt {
+            nummer: index + 2,
+            name: top.name.clone(),
+            antraege: antrag_titel(top),
+            unterpunkte: vec![],
+        });
+    }
+
+    result.push(punkt(
+        normal.len() + 2,
+        "Verschiedenes",
+        TopTyp::Verschiedenes,
+    ));
+
+    result
+}
+
+/// the einladung as a post on the website
+#[derive(Template)]
+#[template(path = "../templates/einladung.md")]
+pub struct EinladungPostTemplate<'a> {
+    pub sitzung: &'a Sitzung,
+    pub tagesordnung: Vec<Tagesordnungspunkt>,
+    pub date_machine: String,
+}
+
+/// the einladung as a mail, including its headers
+#[derive(Template)]
+#[template(path = "../templates/einladung.eml", escape = "none")]
+pub struct EinladungMailTemplate<'a> {
+    pub sitzung: &'a Sitzung,
+    pub tagesordnung: Vec<Tagesordnungspunkt>,
+    pub from: String,
+    pub to: String,
+    pub date: String,
+}
+
+/// the einladung as a short markdown message for chats
+#[derive(Template)]
+#[template(path = "../templates/einladung-chat.md")]
+pub struct EinladungChatTemplate<'a> {
+    pub sitzung: &'a Sitzung,
+    pub tagesordnung: Vec<Tagesordnungspunkt>,
+}
+
+pub fn render_post(sitzung: &Sitzung, now: &DateTime<FixedOffset>) -> Result<String> {
+    let template = EinladungPostTemplate {
+        sitzung,
+        tagesordnung: tagesordnung(sitzung),
+        date_machine: now.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    };
+
+    template
+        .render()
+        .context("failed to render einladung post template")
+}
+
+/// renders the einladung as a mail, with CRLF line endings as required by RFC 5322
+pub fn render_mail(
+    sitzung: &Sitzung,
+    from: &str,
+    to: &str,
+    now: &DateTime<FixedOffset>,
+) -> Result<String> {
+    let template = EinladungMailTemplate {
+        sitzung,
+        tagesordnung: tagesordnung(sitzung),
+        from: from.to_string(),
+        to: to.to_string(),
+        date: now.to_rfc2822(),
+    };
+
+    let mail = template
+        .render()
+        .context("failed to render einladung mail template")?;
+
+    Ok(mail.replace('\n', "\r\n"))
+}
+
+pub fn render_chat(sitzung: &Sitzung) -> Result<String> {
+    let template = EinladungChatTemplate {
+        sitzung,
+        tagesordnung: tagesordnung(sitzung),
+    };
+
+    template
+        .render()
+        .context("failed to render einladung chat template")
+}
+
+// these are functions available within the templates
+mod filters {
+    use chrono::{DateTime, Datelike, FixedOffset, Weekday};
+
+    /// e.g. "Freitag, 27.05.2022 um 18:30 Uhr"
+    pub fn termin(
+        datetime: &DateTime<FixedOffset>,
+        _: &dyn askama::Values,
+    ) -> askama::Result<String> {
+        let wochentag = match datetime.weekday() {
+            Weekday::Mon => "Montag",
+            Weekday::Tue => "Dienstag",
+            Weekday::Wed => "Mittwoch",
+            Weekday::Thu => "Donnerstag",
+            Weekday::Fri => "Freitag",
+            Weekday::Sat => "Samstag",
+            Weekday::Sun => "Sonntag",
+        };
+
+        Ok(format!(
+            "{}, {}",
+            wochentag,
+            datetime.format("%d.%m.%Y um %H:%M Uhr")
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::{Antrag, Sitzung, SitzungTyp, Top, TopTyp};
+
+    static EINLADUNG_POST: &str = include_str!("../tests/einladung.md");
+    static EINLADUNG_MAIL: &str = include_str!("../tests/einladung.eml");
+    static EINLADUNG_CHAT: &str = include_str!("../tests/einladung-chat.md");
+
+    fn sitzung() -> Sitzung {
+        let datetime = |day, hour, minute| {
+            NaiveDate::from_ymd_opt(2022, 5, day)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+                .and_local_timezone(FixedOffset::east_opt(2 * 60 * 60).unwrap())
+                .unwrap()
+        };
+
+        let top = |name: &str, weight, typ, antraege: &[&str]| Top {
+            name: name.to_string(),
+            weight,
+            typ,
+            inhalt: String::new(),
+            antraege: antraege
+                .iter()
+                .map(|titel| Antrag {
+                    titel: titel.to_string(),
+                    antragstext: String::new(),
+                    begruendung: String::new(),
+                    erstellt_am: datetime(17, 12, 0),
+                    antragsteller: vec![],
+                    betrag: None,
+                    haushaltstitel: None,
+                    anhaenge: vec![],
+                })
+                .collect(),
+        };
+
+        Sitzung {
+            id: Uuid::nil(),
+            datetime: datetime(27, 18, 30),
+            typ: SitzungTyp::Normal,
+            tops: vec![
+                top("Volt Zapfanlage", 2, TopTyp::Normal, &["Tank", "Hahn"]),
+                top("Wahl der Protokollführung", 0, TopTyp::Regularia, &[]),
+                top("Blumen für Valentin", 1, TopTyp::Normal, &[]),
+            ],
+            antragsfrist: Some(datetime(24, 18, 30)),
+        }
+    }
+
+    #[test]
+    fn tagesordnung() {
+        let result: Vec<(usize, String)> = super::tagesordnung(&sitzung())
+            .into_iter()
+            .map(|t| (t.nummer, t.name))
+            .collect();
+
+        let expected = vec![
+            (0, "Regularia".to_string()),
+            (1, "Berichte, Mail und Post".to_string()),
+            (2, "Blumen für Valentin".to_string()),
+            (3, "Volt Zapfanlage".to_string()),
+            (4, "Verschiedenes".to_string()),
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn tagesordnung_unterpunkte() {
+        let mut sitzung = sitzung();
+        let bericht = Top {
+            name: "Bericht vom Fakultätsrat".to_string(),
+            typ: TopTyp::Bericht,
+            ..sitzung.tops[1].clone()
+        };
+        sitzung.tops.push(bericht);
+
+        let result = super::tagesordnung(&sitzung);
+
+        let unterpunkte = |nummer: usize| -> Vec<String> {
+            result[nummer]
+                .unterpunkte
+                .iter()
+                .map(|u| u.name.clone())
+                .collect()
+        };
+
+        assert_eq!(unterpunkte(0), vec!["Wahl der Protokollführung"]);
+        assert_eq!(unterpunkte(1), vec!["Bericht vom Fakultätsrat"]);
+        assert_eq!(unterpunkte(2), Vec::<String>::new());
+        assert_eq!(unterpunkte(4), Vec::<String>::new());
+    }
+
+    #[test]
+    fn render_post() {
+        let now = NaiveDate::from_ymd_opt(2022, 5, 20)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(2 * 60 * 60).unwrap())
+            .unwrap();
+
+        let result = super::render_post(&sitzung(), &now).unwrap();
+
+        assert_eq!(result, EINLADUNG_POST);
+    }
+
+    #[test]
+    fn render_mail() {
+        let now = NaiveDate::from_ymd_opt(2022, 5, 20)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(2 * 60 * 60).unwrap())
+            .unwrap();
+
+        let result =
+            super::render_mail(&sitzung(), "fsr@example.org", "studis@example.org", &now).unwrap();
+
+        assert_eq!(result, EINLADUNG_MAIL.replace('\n', "\r\n"));
+
+        let konsti = Sitzung {
+            typ: SitzungTyp::Konsti,
+            ..sitzung()
+        };
+
+        let konsti_mail =
+            super::render_mail(&konsti, "fsr@example.org", "studis@example.org", &now).unwrap();
+
+        assert!(konsti_mail
+            .contains("Subject: Einladung zur konstituierenden Sitzung am 27.05.2022\r\n"));
+    }
+
+    #[test]
+    fn render_chat() {
+        let result = super::render_chat(&sitzung()).unwrap();
+
+        assert_eq!(result, EINLADUNG_CHAT);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+
+use crate::{protokoll::parse::ParsedProtokoll, Betrag};
+
+/// the haushaltstitel used for accepted finanzanträge which dont name one
+pub const OHNE_HAUSHALTSTITEL: &str = "ohne Haushaltstitel";
+
+/// a semester, e.g. "SoSe 2022" or "WiSe 2022/23"
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Semester {
+    Sommer(i32),
+    Winter(i32),
+}
+
+impl Semester {
+    /// the summer semester runs from april to september, the winter semester from
+    /// october to march
+    pub fn of(date: NaiveDate) -> Self {
+        match date.month() {
+            4..=9 => Self::Sommer(date.year()),
+            10..=12 => Self::Winter(date.year()),
+            _ => Self::Winter(date.year() - 1),
+        }
+    }
+}
+
+impl Display for Semester {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sommer(year) => f.write_fmt(format_args!("SoSe {}", year)),
+            Self::Winter(year) => {
+                f.write_fmt(format_args!("WiSe {}/{:02}", year, (year + 1) % 100))
+            }
+        }
+    }
+}
+
+/// the budget per semester and haushaltstitel, as read from the budget file:
+///
+/// ```toml
+/// ["SoSe 2022"]
+/// Erstiarbeit = "1.500,00"
+/// Fachschaftsfahrt = 2000
+/// ```
+pub type Budget = BTreeMap<String, BTreeMap<String, Betrag>>;
+
+pub fn load_budget(path: &Path) -> Result<Budget> {
+    let content = fs::read_to_string(path).context("unable to read budget file")?;
+
+    toml::from_str(content.as_str()).context("unable to parse budget file")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Posten {
+    pub semester: Semester,
+    pub haushaltstitel: String,
+    pub ausgaben: Betrag,
+    pub budget: Option<Betrag>,
+}
+
+impl Posten {
+    pub fn is_overspent(&self) -> bool {
+        self.budget.is_some_and(|budget| self.ausgaben > budget)
+    }
+
+    pub fn rest(&self) -> Option<Betrag> {
+        self.budget.map(|budget| budget - self.ausgaben)
+    }
+}
+
+/// sums up the amounts of all accepted finanzanträge per semester and haushaltstitel
+/// and compares them against the budget. haushaltstitel which only appear in the
+/// budget are included as well.
+///
+/// drafts and votes without any counts, like the placeholders of the template, are
+/// left out.
+pub fn summarize(protokolle: &[ParsedProtokoll], budget: &Budget) -> Vec<Posten> {
+    let mut ausgaben: BTreeMap<(Semester, String), Betrag> = BTreeMap::new();
+
+    for protokoll in protokolle.iter().filter(|p| !p.draft) {
+        let semester = Semester::of(protokoll.date);
+
+        let accepted = protokoll.antraege().filter(|a| {
+            a.abstimmung
+                .as_ref()
+                .is_some_and(|v| v.angenommen && v.has_counts())
+        });
+
+        for antrag in accepted {
+            let Some(betrag) = antrag.betrag else {
+                continue;
+            };
+
+            let haushaltstitel = antrag
+                .haushaltstitel
+                .clone()
+                .unwrap_or_else(|| OHNE_HAUSHALTSTITEL.to_string());
+
+            let sum = ausgaben.entry((semester, haushaltstitel)).or_default();
+            *sum = *sum + betrag;
+        }
+    }
+
+    let mut result: Vec<Posten> = ausgaben
+        .into_iter()
+        .map(|((semester, haushaltstitel), sum)| Posten {
+            budget: budget
+                .get(&semester.to_string())
+                .and_then(|titel| titel.get(&haushaltstitel))
+                .copied(),
+            semester,
+            haushaltstitel,
+            ausgaben: sum,
+        })
+        .collect();
+
+    let semesters: Vec<Semester> = result.iter().map(|p| p.semester).collect();
+
+    for (semester_name, titel) in budget {
+        let Some(semester) = semesters.iter().find(|s| s.to_string() == *semester_name) else {
+            continue;
+        };
+
+        for (haushaltstitel, betrag) in titel {
+            let exists = result
+                .iter()
+                .any(|p| p.semester == *semester && p.haushaltstitel == *haushaltstitel);
+
+            if !exists {
+                result.push(Posten {
+                    semester: *semester,
+                    haushaltstitel: haushaltstitel.to_string(),
+                    ausgaben: Betrag::default(),
+                    budget: Some(*betrag),
+                });
+            }
+        }
+    }
+
+    result.sort_by(|a, b| (a.semester, &a.haushaltstitel).cmp(&(b.semester, &b.haushaltstitel)));
+
+    result
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::{Budget, Posten, Semester};
+    use crate::{protokoll::parse, Betrag};
+
+    #[test]
+    fn semester() {
+        let semester = |y, m, d| Semester::of(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        assert_eq!(semester(2022, 4, 1), Semester::Sommer(2022));
+        assert_eq!(semester(2022, 9, 30), Semester::Sommer(2022));
+        assert_eq!(semester(2022, 10, 1), Semester::Winter(2022));
+        assert_eq!(semester(2023, 3, 31), Semester::Winter(2022));
+
+        assert_eq!(Semester::Sommer(2022).to_string(), "SoSe 2022");
+        assert_eq!(Semester::Winter(2099).to_string(), "WiSe 2099/00");
+    }
+
+    #[test]
+    fn summarize() {
+        let protokoll = |date: &str, draft: bool, antraege: &str| {
+            parse::parse(&format!(
+                "---\ndate: \"{}\"\ndraft: {}\n---\n\n## Top 2: Finanzen\n{}",
+                date, draft, antraege
+            ))
+            .unwrap()
+        };
+
+        let antrag_with = |betrag: &str, titel: &str, lang: &str, ergebnis: &str| {
+            format!(
+                "\n### Antrag: Geld\n\nBetrag: {}  \nHaushaltstitel: {}\n\n```{}\nGeld\nAbstimmung: {}\n```\n",
+                betrag, titel, lang, ergebnis
+            )
+        };
+        let antrag = |betrag: &str, titel: &str, lang: &str| {
+            antrag_with(
+                betrag,
+                titel,
+                lang,
+                "5 Zustimmen, 1 Gegenstimmen, 0 Enthaltungen",
+            )
+        };
+
+        let protokolle = vec![
+            protokoll(
+                "2022-05-27",
+                false,
+                &[
+                    antrag("250,00 €", "Erstiarbeit", "vote-success"),
+                    antrag("1.000,00 €", "Erstiarbeit", "vote-failure"),
+                ]
+                .concat(),
+            ),
+            protokoll(
+                "2022-06-03",
+                false,
+                &[
+                    antrag("1.300,00 €", "Erstiarbeit", "vote-success"),
+                    antrag("80,00 €", "Kaffee", "vote-success"),
+                    // the placeholder of the template
+                    antrag_with(
+                        "500,00 €",
+                        "Kaffee",
+                        "vote-success",
+                        "n Zustimmen, n Gegenstimmen, n Enthaltungen",
+                    ),
+                ]
+                .concat(),
+            ),
+            protokoll(
+                "2022-06-10",
+                true,
+                &antrag("30,00 €", "Kaffee", "vote-success"),
+            ),
+            protokoll(
+                "2022-11-03",
+                false,
+                &antrag("20,00 €", "Kaffee", "vote-success"),
+            ),
+        ];
+
+        let budget: Budget = toml::from_str(
+            r#"
+            ["SoSe 2022"]
+            Erstiarbeit = "1.500,00"
+            Kaffee = 100
+            Fachschaftsfahrt = 2000
+            "#,
+        )
+        .unwrap();
+
+        let result = super::summarize(&protokolle, &budget);
+
+        let expected = vec![
+            Posten {
+                semester: Semester::Sommer(2022),
+                haushaltstitel: "Erstiarbeit".to_string(),
+                ausgaben: Betrag(155_000),
+                budget: Some(Betrag(150_000)),
+            },
+            Posten {
+                semester: Semester::Sommer(2022),
+                haushaltstitel: "Fachschaftsfahrt".to_string(),
+                ausgaben: Betrag(0),
+                budget: Some(Betrag(200_000)),
+            },
+            Posten {
+                semester: Semester::Sommer(2022),
+                haushaltstitel: "Kaffee".to_string(),
+                ausgaben: Betrag(8000),
+                budget: Some(Betrag(10000)),
+            },
+            Posten {
+                semester: Semester::Winter(2022),
+                haushaltstitel: "Kaffee".to_string(),
+                ausgaben: Betrag(2000),
+                budget: None,
+            },
+        ];
+
+        assert_eq!(result, expected);
+        assert!(result[0].is_overspent());
+        assert_eq!(result[0].rest(), Some(Betrag(-5000)));
+        assert!(!result[2].is_overspent());
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+fn git(root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .context("unable to spawn git process")?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// fails if the index of the repository at `root` already contains staged changes,
+/// so that we dont accidentally commit them alongside our own
+pub fn ensure_nothing_staged(root: &Path) -> Result<()> {
+    let staged = git(root, &["diff", "--cached", "--name-only"])?;
+
+    if !staged.trim().is_empty() {
+        bail!(
+            "refusing to commit, the repository has unrelated staged changes:\n{}",
+            staged.trim_end()
+        );
+    }
+
+    Ok(())
+}
+
+/// fails if the repository at `root` already has a branch named `name`
+pub fn ensure_branch_missing(root: &Path, name: &str) -> Result<()> {
+    let existing = git(root, &["branch", "--list", name])?;
+
+    if !existing.trim().is_empty() {
+        bail!("refusing to commit, the branch '{}' already exists", name);
+    }
+
+    Ok(())
+}
+
+/// creates and switches to a new branch
+pub fn create_branch(root: &Path, name: &str) -> Result<()> {
+    git(root, &["switch", "--create", name])?;
+
+    Ok(())
+}
+
+/// stages `path` and commits it with `message`
+pub fn commit_file(root: &Path, path: &Path, message: &str) -> Result<()> {
+    let path_str = path.to_string_lossy();
+
+    git(root, &["add", "--", &path_str])?;
+    git(root, &["commit", "--message", message, "--", &path_str])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    use std::fs;
+    use std::path::Path;
+
+    fn init_repo(root: &Path) {
+        super::git(root, &["init", "--quiet"]).unwrap();
+        super::git(root, &["config", "user.name", "prototool"]).unwrap();
+        super::git(root, &["config", "user.email", "prototool@localhost"]).unwrap();
+    }
+
+    #[test]
+    fn refuse_staged_changes() {
+        let tmpdir = tempdir().unwrap();
+        init_repo(tmpdir.path());
+
+        super::ensure_nothing_staged(tmpdir.path()).unwrap();
+
+        fs::write(tmpdir.path().join("unrelated.md"), "").unwrap();
+        super::git(tmpdir.path(), &["add", "unrelated.md"]).unwrap();
+
+        assert!(super::ensure_nothing_staged(tmpdir.path()).is_err());
+    }
+
+    #[test]
+    fn refuse_existing_branch() {
+        let tmpdir = tempdir().unwrap();
+        init_repo(tmpdir.path());
+
+        let path = tmpdir.path().join("protokoll.de.md");
+        fs::write(&path, "").unwrap();
+        super::commit_file(tmpdir.path(), &path, "add protokoll").unwrap();
+
+        super::ensure_branch_missing(tmpdir.path(), "protokoll/2022-05-27").unwrap();
+
+        super::git(tmpdir.path(), &["branch", "protokoll/2022-05-27"]).unwrap();
+
+        assert!(super::ensure_branch_missing(tmpdir.path(), "protokoll/2022-05-27").is_err());
+    }
+
+    #[test]
+    fn commit_file_on_branch() {
+        let tmpdir = tempdir().unwrap();
+        init_repo(tmpdir.path());
+
+        let path = tmpdir.path().join("protokoll.de.md");
+        fs::write(&path, "").unwrap();
+
+        super::create_branch(tmpdir.path(), "protokoll/2022-05-27").unwrap();
+        super::commit_file(tmpdir.path(), &path, "add protokoll").unwrap();
+
+        let branch = super::git(tmpdir.path(), &["branch", "--show-current"]).unwrap();
+        let message = super::git(tmpdir.path(), &["log", "--format=%s"]).unwrap();
+
+        assert_eq!(branch.trim(), "protokoll/2022-05-27");
+        assert_eq!(message.trim(), "add protokoll");
+    }
+}
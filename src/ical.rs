@@ -0,0 +1,348 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{Event, EventTime, Sitzung};
+
+/// how long a sitzung is assumed to take, since the backend doesnt know about its end
+const SITZUNG_DURATION: &str = "PT2H";
+
+/// how long before the antragsfrist calendar apps should remind about it
+const ANTRAGSFRIST_REMINDER: &str = "-P1D";
+
+const PRODID: &str = "-//fscs//prototool//DE";
+
+/// renders the sitzungen, their antragsfristen and the events into an iCalendar file.
+/// events which are already over at `now` are left out.
+pub fn render_calendar(sitzungen: &[Sitzung], events: &[Event], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+
+    for sitzung in sitzungen {
+        lines.extend(sitzung_lines(sitzung, now));
+        lines.extend(antragsfrist_lines(sitzung, now));
+    }
+
+    for event in events.iter().filter(|e| !is_over(e, now)) {
+        lines.extend(event_lines(event, now));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut result = String::new();
+
+    for line in lines {
+        result.push_str(&fold_line(&line));
+        result.push_str("\r\n");
+    }
+
+    result
+}
+
+fn sitzung_lines(sitzung: &Sitzung, now: DateTime<Utc>) -> Vec<String> {
+    let mut description = "Tops:".to_string();
+
+    for (index, top) in sitzung.tops.iter().enumerate() {
+        description.push_str(&format!("\n{}. {}", index + 1, top.name));
+    }
+
+    if sitzung.tops.is_empty() {
+        description.push_str("\nnoch keine");
+    }
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:sitzung-{}@prototool", sitzung.id),
+        format!("DTSTAMP:{}", format_datetime(&now)),
+        format!("DTSTART:{}", format_datetime(&sitzung.datetime)),
+        format!("DURATION:{}", SITZUNG_DURATION),
+        format!("SUMMARY:{}", escape_text(sitzung.typ.bezeichnung())),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn antragsfrist_lines(sitzung: &Sitzung, now: DateTime<Utc>) -> Vec<String> {
+    let Some(antragsfrist) = sitzung.antragsfrist else {
+        return vec![];
+    };
+
+    let summary = format!(
+        "Antragsfrist für die {} am {}",
+        sitzung.typ.bezeichnung(),
+        sitzung.datetime.format("%d.%m.%Y")
+    );
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:antragsfrist-{}@prototool", sitzung.id),
+        format!("DTSTAMP:{}", format_datetime(&now)),
+        format!("DTSTART:{}", format_datetime(&antragsfrist)),
+        format!("SUMMARY:{}", escape_text(&summary)),
+        "BEGIN:VALARM".to_string(),
+        "ACTION:DISPLAY".to_string(),
+        format!("TRIGGER:{}", ANTRAGSFRIST_REMINDER),
+        format!("DESCRIPTION:{}", escape_text(&summary)),
+        "END:VALARM".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn event_lines(event: &Event, now: DateTime<Utc>) -> Vec<String> {
+    let title = event.title.as_deref().unwrap_or("Veranstaltung");
+
+    // events dont have an id, so we derive a stable one from their start and title
+    let slug: String = title
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+
+    let start_stamp = match event.start {
+        EventTime::DateTime(start) => start.format("%Y%m%dT%H%M%S").to_string(),
+        EventTime::Date(start) => start.format("%Y%m%d").to_string(),
+    };
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:event-{}-{}@prototool", start_stamp, slug),
+        format!("DTSTAMP:{}", format_datetime(&now)),
+        format!("DTSTART{}", format_event_time(&event.start)),
+    ];
+
+    // DTSTART and DTEND have to be of the same value type, so mixed ones lose their end
+    let matching_end = event
+        .end
+        .filter(|end| is_all_day(end) == is_all_day(&event.start));
+
+    if let Some(end) = &matching_end {
+        lines.push(format!("DTEND{}", format_event_time(end)));
+    }
+
+    lines.push(format!("SUMMARY:{}", escape_text(title)));
+
+    if let Some(location) = &event.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+
+    lines.push("END:VEVENT".to_string());
+
+    lines
+}
+
+/// whether `event` ended before `now`. events without an end count as over once
+/// they started, all-day events once their exclusive end date is reached.
+fn is_over(event: &Event, now: DateTime<Utc>) -> bool {
+    match (event.end, event.start) {
+        (Some(EventTime::DateTime(end)), _) | (None, EventTime::DateTime(end)) => end < now,
+        (Some(EventTime::Date(end)), _) => end <= now.date_naive(),
+        (None, EventTime::Date(start)) => start < now.date_naive(),
+    }
+}
+
+fn format_datetime<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String {
+    datetime
+        .with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+const fn is_all_day(time: &EventTime) -> bool {
+    matches!(time, EventTime::Date(_))
+}
+
+/// formats the value of a DTSTART or DTEND property, including its parameters
+fn format_event_time(time: &EventTime) -> String {
+    match time {
+        EventTime::DateTime(datetime) => format!(":{}", format_datetime(datetime)),
+        EventTime::Date(date) => format!(";VALUE=DATE:{}", date.format("%Y%m%d")),
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// folds lines longer than 75 octets, as required by RFC 5545
+fn fold_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut line_len = 0;
+
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            line_len = 1;
+        }
+
+        result.push(c);
+        line_len += c.len_utf8();
+    }
+
+    result
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use crate::{Event, EventTime, Sitzung, SitzungTyp, Top, TopTyp};
+
+    static CALENDAR: &str = include_str!("../tests/sitzungen.ics");
+
+    fn tz_offset() -> FixedOffset {
+        FixedOffset::east_opt(2 * 60 * 60).unwrap()
+    }
+
+    #[test]
+    fn render_calendar() {
+        let sitzung = Sitzung {
+            id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
+            datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(18, 30, 0)
+                .unwrap()
+                .and_local_timezone(tz_offset())
+                .unwrap(),
+            typ: SitzungTyp::Normal,
+            tops: vec![Top {
+                name: "Blumen für Valentin, Jonas und Marcel".to_string(),
+                weight: 1,
+                typ: TopTyp::Normal,
+                inhalt: String::new(),
+                antraege: vec![],
+            }],
+            antragsfrist: Some(
+                NaiveDate::from_ymd_opt(2022, 5, 25)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(tz_offset())
+                    .unwrap(),
+            ),
+        };
+
+        let events = vec![
+            Event {
+                title: Some("Grillen".to_string()),
+                location: None,
+                start: EventTime::DateTime(
+                    NaiveDate::from_ymd_opt(2022, 5, 13)
+                        .unwrap()
+                        .and_hms_opt(17, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+                end: None,
+            },
+            Event {
+                title: Some("Spieleabend".to_string()),
+                location: Some("25.12.O2.35".to_string()),
+                start: EventTime::DateTime(
+                    NaiveDate::from_ymd_opt(2022, 5, 30)
+                        .unwrap()
+                        .and_hms_opt(19, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+                end: None,
+            },
+            Event {
+                title: Some("Ersti-Fahrt".to_string()),
+                location: None,
+                start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 6, 10).unwrap()),
+                end: Some(EventTime::Date(
+                    NaiveDate::from_ymd_opt(2022, 6, 13).unwrap(),
+                )),
+            },
+        ];
+
+        let now = NaiveDate::from_ymd_opt(2022, 5, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        let result = super::render_calendar(&[sitzung], &events, now);
+
+        assert_eq!(result, CALENDAR.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn mixed_event_times() {
+        let event = Event {
+            title: Some("Ersti-Fahrt".to_string()),
+            location: None,
+            start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 6, 10).unwrap()),
+            end: Some(EventTime::DateTime(
+                NaiveDate::from_ymd_opt(2022, 6, 13)
+                    .unwrap()
+                    .and_hms_opt(16, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(tz_offset())
+                    .unwrap(),
+            )),
+        };
+
+        let now = NaiveDate::from_ymd_opt(2022, 5, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        let result = super::event_lines(&event, now);
+
+        assert!(result.contains(&"DTSTART;VALUE=DATE:20220610".to_string()));
+        assert!(!result.iter().any(|l| l.starts_with("DTEND")));
+    }
+
+    #[test]
+    fn is_over() {
+        let date = |day| NaiveDate::from_ymd_opt(2022, 5, day).unwrap();
+
+        let all_day = |end| Event {
+            title: Some("Tag der offenen Tür".to_string()),
+            location: None,
+            start: EventTime::Date(date(19)),
+            end,
+        };
+
+        let now = date(20).and_hms_opt(12, 0, 0).unwrap().and_utc();
+
+        assert!(super::is_over(
+            &all_day(Some(EventTime::Date(date(20)))),
+            now
+        ));
+        assert!(!super::is_over(
+            &all_day(Some(EventTime::Date(date(21)))),
+            now
+        ));
+        assert!(super::is_over(&all_day(None), now));
+
+        let today = Event {
+            start: EventTime::Date(date(20)),
+            ..all_day(None)
+        };
+
+        assert!(!super::is_over(&today, now));
+    }
+
+    #[test]
+    fn fold_line() {
+        let line = "D".repeat(80);
+
+        let result = super::fold_line(&line);
+
+        assert_eq!(result, format!("{}\r\n {}", "D".repeat(75), "D".repeat(5)));
+    }
+}
@@ -5,12 +5,28 @@
 #![warn(clippy::nursery)]
 #![allow(clippy::needless_return)]
 
+pub mod api;
+pub mod config;
+pub mod einladung;
+pub mod finanzen;
+pub mod git;
+pub mod ical;
+pub mod mock;
+pub mod pad;
 pub mod post;
 pub mod protokoll;
+pub mod schema;
+pub mod timezone;
+pub mod todos;
+pub mod webhook;
+
+#[cfg(test)]
+mod test_server;
 
 pub use protokoll::{
+    betrag::Betrag,
+    events::{Event, EventTime},
+    person::{Abmeldung, AbmeldungGrund, Person, PersonWithAbmeldung},
+    sitzung::{Anhang, Antrag, Sitzung, SitzungTyp, Top, TopTyp},
     ProtokollTemplate,
-    events::Event,
-    person::{Abmeldung, Person, PersonWithAbmeldung},
-    sitzung::{Antrag, Sitzung, SitzungTyp, Top, TopTyp},
 };
@@ -0,0 +1,337 @@
+//! a fake of the website api serving json fixtures, to try prototool without a backend
+//! and to test the fetchers end to end
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use serde_json::Value;
+use url::Url;
+
+static SITZUNGEN: &str = include_str!("../tests/mock/sitzungen.json");
+static PERSONS: &str = include_str!("../tests/mock/persons.json");
+static ABMELDUNGEN: &str = include_str!("../tests/mock/abmeldungen.json");
+static EVENTS: &str = include_str!("../tests/mock/events.json");
+
+const NOT_FOUND: &str = "404 Not Found";
+const BAD_REQUEST: &str = "400 Bad Request";
+
+/// the data served by the mock api. persons carry a list of their 'roles' and
+/// abmeldungen the 'sitzung_id' they belong to, which the real api keeps in its database.
+#[derive(Debug, Clone)]
+pub struct MockBackend {
+    sitzungen: Vec<Value>,
+    persons: Vec<Value>,
+    abmeldungen: Vec<Value>,
+    events: Vec<Value>,
+}
+
+impl MockBackend {
+    /// the fixtures shipped with prototool
+    pub fn builtin() -> Result<Self> {
+        Ok(Self {
+            sitzungen: parse_fixture("sitzungen.json", SITZUNGEN)?,
+            persons: parse_fixture("persons.json", PERSONS)?,
+            abmeldungen: parse_fixture("abmeldungen.json", ABMELDUNGEN)?,
+            events: parse_fixture("events.json", EVENTS)?,
+        })
+    }
+
+    /// loads 'sitzungen.json', 'persons.json', 'abmeldungen.json' and 'events.json'
+    /// from `dir`. missing files are served as empty lists.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let load_fixture = |name: &str| -> Result<Vec<Value>> {
+            let path = dir.join(name);
+
+            if !path.exists() {
+                return Ok(vec![]);
+            }
+
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            parse_fixture(name, &content)
+        };
+
+        Ok(Self {
+            sitzungen: load_fixture("sitzungen.json")?,
+            persons: load_fixture("persons.json")?,
+            abmeldungen: load_fixture("abmeldungen.json")?,
+            events: load_fixture("events.json")?,
+        })
+    }
+
+    /// moves all dates by whole weeks, so that the first sitzung lies within the week
+    /// after `now`. weekdays and times stay the same.
+    pub fn shift_to(&mut self, now: DateTime<FixedOffset>) {
+        let Some(first) = self
+            .sitzungen
+            .iter()
+            .filter_map(|s| datetime_field(s, "datetime"))
+            .min()
+        else {
+            return;
+        };
+
+        let offset = TimeDelta::weeks((now - first).num_weeks() + 1);
+
+        for value in self
+            .sitzungen
+            .iter_mut()
+            .chain(&mut self.abmeldungen)
+            .chain(&mut self.events)
+        {
+            shift_dates(value, offset);
+        }
+    }
+
+    /// answers a GET request for `target`, e.g. '/api/persons/by-role/?role=Rat'.
+    /// fails with the status line to answer with.
+    pub fn respond(&self, target: &str) -> Result<Value, &'static str> {
+        let url = Url::parse("http://localhost/")
+            .and_then(|base| base.join(target))
+            .map_err(|_| BAD_REQUEST)?;
+
+        let query = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        let segments: Vec<&str> = url.path().trim_matches('/').split('/').collect();
+
+        let result: Vec<&Value> = match segments.as_slice() {
+            ["api", "sitzungen", "after"] => {
+                let timestamp = query("timestamp")
+                    .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                    .ok_or(BAD_REQUEST)?;
+                let limit = match query("limit") {
+                    Some(limit) => limit.parse().map_err(|_| BAD_REQUEST)?,
+                    None => usize::MAX,
+                };
+
+                let mut sitzungen: Vec<(DateTime<FixedOffset>, &Value)> = self
+                    .sitzungen
+                    .iter()
+                    .filter_map(|s| datetime_field(s, "datetime").map(|d| (d, s)))
+                    .filter(|(datetime, _)| *datetime >= timestamp)
+                    .collect();
+
+                sitzungen.sort_by_key(|(datetime, _)| *datetime);
+
+                sitzungen.into_iter().take(limit).map(|(_, s)| s).collect()
+            }
+            ["api", "persons", "by-role"] => {
+                let role = Value::String(query("role").ok_or(BAD_REQUEST)?);
+
+                self.persons
+                    .iter()
+                    .filter(|p| p["roles"].as_array().is_some_and(|r| r.contains(&role)))
+                    .collect()
+            }
+            ["api", "sitzungen", id, "abmeldungen"] => self
+                .abmeldungen
+                .iter()
+                .filter(|a| a["sitzung_id"].as_str() == Some(id))
+                .collect(),
+            ["api", "calendar", "events"] => self.events.iter().collect(),
+            _ => return Err(NOT_FOUND),
+        };
+
+        Ok(Value::Array(result.into_iter().cloned().collect()))
+    }
+
+    /// answers the request on `stream` and returns its request line and the status
+    /// it was answered with
+    pub fn handle(&self, stream: TcpStream) -> Result<(String, &'static str)> {
+        let mut reader = BufReader::new(stream);
+
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .context("unable to read request")?;
+
+        // the headers arent needed, but have to be read before answering
+        loop {
+            let mut header = String::new();
+            let read = reader
+                .read_line(&mut header)
+                .context("unable to read request")?;
+
+            if read == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let request_line = line.trim().to_string();
+
+        let (status, body) = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["GET", target, _] => match self.respond(target) {
+                Ok(value) => ("200 OK", value.to_string()),
+                Err(status) => (status, String::new()),
+            },
+            [_, _, _] => ("405 Method Not Allowed", String::new()),
+            _ => bail!("malformed request '{}'", request_line),
+        };
+
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+        .context("unable to write response")?;
+
+        Ok((request_line, status))
+    }
+}
+
+fn parse_fixture(name: &str, content: &str) -> Result<Vec<Value>> {
+    serde_json::from_str(content).with_context(|| format!("'{}' is no list of objects", name))
+}
+
+fn datetime_field(value: &Value, field: &str) -> Option<DateTime<FixedOffset>> {
+    value[field]
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+}
+
+/// adds `offset` to every string within `value`, which is a datetime or a date
+fn shift_dates(value: &mut Value, offset: TimeDelta) {
+    match value {
+        Value::String(s) => {
+            if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+                *s = (datetime + offset).to_rfc3339();
+            } else if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+                *s = (date + offset).format("%Y-%m-%d").to_string();
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| shift_dates(v, offset)),
+        Value::Object(fields) => fields.values_mut().for_each(|v| shift_dates(v, offset)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use askama::Template;
+    use chrono::{DateTime, Datelike, NaiveDate, Weekday};
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use url::Url;
+
+    use super::MockBackend;
+    use crate::{
+        api::ApiClient,
+        config::{ApiConfig, Config},
+        protokoll::{events, person, sitzung},
+        ProtokollTemplate,
+    };
+
+    static PROTOKOLL_FROM_MOCK: &str = include_str!("../tests/protokoll-from-mock.md");
+
+    fn names(value: &Value, field: &str) -> Vec<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v[field].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn respond() {
+        let backend = MockBackend::builtin().unwrap();
+
+        let sitzungen = backend
+            .respond("/api/sitzungen/after/?timestamp=2022-05-28T00%3A00%3A00%2B02%3A00&limit=1")
+            .unwrap();
+        let beratend = backend
+            .respond("/api/persons/by-role/?role=Beratendes+Mitglied")
+            .unwrap();
+        let abmeldungen = backend
+            .respond("/api/sitzungen/5d1cbc5e-8a1f-4e0a-9d39-2f4b0f6a3c01/abmeldungen/")
+            .unwrap();
+
+        assert_eq!(
+            names(&sitzungen, "datetime"),
+            vec!["2022-06-03T18:30:00+02:00"]
+        );
+        assert_eq!(names(&beratend, "name"), vec!["Lea"]);
+        assert_eq!(abmeldungen.as_array().unwrap().len(), 2);
+
+        assert_eq!(backend.respond("/api/persons/"), Err(super::NOT_FOUND));
+        assert_eq!(
+            backend.respond("/api/sitzungen/after/?timestamp=morgen"),
+            Err(super::BAD_REQUEST)
+        );
+    }
+
+    #[test]
+    fn shift_to() {
+        let mut backend = MockBackend::builtin().unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-01-10T12:00:00+01:00").unwrap();
+
+        backend.shift_to(now);
+
+        let sitzungen = backend
+            .respond("/api/sitzungen/after/?timestamp=2024-01-10T12%3A00%3A00%2B01%3A00")
+            .unwrap();
+        let first = DateTime::parse_from_rfc3339(&names(&sitzungen, "datetime")[0]).unwrap();
+        let events = backend.respond("/api/calendar/events/").unwrap();
+
+        assert_eq!(sitzungen.as_array().unwrap().len(), 3);
+        assert_eq!(first.weekday(), Weekday::Fri);
+        assert!(first > now && first - now < chrono::TimeDelta::weeks(1));
+        assert_eq!(
+            NaiveDate::parse_from_str(&names(&events, "start")[1], "%Y-%m-%d")
+                .unwrap()
+                .weekday(),
+            Weekday::Fri
+        );
+    }
+
+    #[test]
+    fn fetch_from_mock() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let backend = MockBackend::builtin().unwrap();
+
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming().take(4) {
+                backend.handle(stream.unwrap()).unwrap();
+            }
+        });
+
+        let api = ApiClient::new(url, &ApiConfig::default()).unwrap();
+        let sitzung_date = DateTime::parse_from_rfc3339("2022-05-27T00:00:00+02:00").unwrap();
+
+        let sitzung = sitzung::fetch_sitzung(&api, sitzung_date).unwrap();
+        let raete = person::fetch_raete(&api).unwrap();
+        let abmeldungen = person::fetch_abmeldungen(&api, &sitzung).unwrap();
+        let calendar_events = events::fetch_calendar_events(&api).unwrap();
+
+        handle.join().unwrap();
+
+        let config = Config::default();
+        let template = ProtokollTemplate::assemble(
+            &config,
+            sitzung,
+            &raete,
+            abmeldungen,
+            calendar_events,
+            &config.events,
+            None,
+        );
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_FROM_MOCK);
+    }
+}
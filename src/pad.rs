@@ -0,0 +1,204 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use reqwest::{blocking::Client, header::CONTENT_TYPE, StatusCode};
+use url::Url;
+
+use crate::webhook;
+
+/// the hedgedoc instance protokolle are written in
+pub const DEFAULT_PAD_URL: &str = "https://pad.hhu.de/";
+
+/// the name of the note for the sitzung at `datetime`, e.g. '2022-05-27-FSR-Informatik'
+pub fn note_alias(datetime: &DateTime<FixedOffset>) -> String {
+    datetime.format("%Y-%m-%d-FSR-Informatik").to_string()
+}
+
+pub fn note_url(pad_url: &Url, alias: &str) -> Result<Url> {
+    pad_url
+        .join(alias)
+        .with_context(|| format!("invalid note alias '{}'", alias))
+}
+
+/// creates a note named `alias` with `content`. this needs 'allowFreeURL' to be
+/// enabled on the hedgedoc instance. returns false, if the note already exists.
+pub fn create_note(client: &Client, pad_url: &Url, alias: &str, content: &str) -> Result<bool> {
+    let endpoint = pad_url.join(format!("new/{}", alias).as_str())?;
+
+    let response = client
+        .post(endpoint)
+        .header(CONTENT_TYPE, "text/markdown")
+        .body(content.to_string())
+        .send()
+        .context("unable to create note")?;
+
+    match response.status() {
+        StatusCode::CONFLICT => Ok(false),
+        status if status.is_success() || status.is_redirection() => Ok(true),
+        status => bail!("unable to create note: {}", status),
+    }
+}
+
+/// how far the note of a sitzung was prepared by [prepare_note]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteState {
+    /// the note was created, but not announced yet
+    Created,
+    /// the note was created and announced, if there is a webhook
+    Announced,
+    /// the note existed before, so it isnt announced
+    Existed,
+}
+
+impl NoteState {
+    /// whether nothing is left to do for the note
+    pub const fn is_done(self) -> bool {
+        matches!(self, Self::Announced | Self::Existed)
+    }
+}
+
+/// creates the note `alias` and posts `announcement` to the webhook.
+///
+/// the note is rendered and created unless `state` says it was created already.
+/// `state` is updated after each step, so a failed announcement is retried by the
+/// next call without creating the note again.
+pub fn prepare_note(
+    client: &Client,
+    pad_url: &Url,
+    alias: &str,
+    render: impl FnOnce() -> Result<String>,
+    announcement: Option<(&Url, &str)>,
+    state: &mut Option<NoteState>,
+) -> Result<()> {
+    if state.is_none() {
+        let created = create_note(client, pad_url, alias, &render()?)?;
+
+        *state = Some(if created {
+            NoteState::Created
+        } else {
+            NoteState::Existed
+        });
+    }
+
+    if *state == Some(NoteState::Created) {
+        if let Some((webhook_url, message)) = announcement {
+            webhook::post_message(client, webhook_url, message)?;
+        }
+
+        *state = Some(NoteState::Announced);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use reqwest::blocking::Client;
+
+    use super::NoteState;
+    use crate::test_server::{stub_responses, stub_server};
+
+    #[test]
+    fn create_note() {
+        let (url, handle) = stub_server("201 Created");
+
+        let created =
+            super::create_note(&Client::new(), &url, "2022-05-27-FSR", "# Protokoll").unwrap();
+
+        let (request_line, body) = handle.join().unwrap();
+
+        assert!(created);
+        assert_eq!(request_line, "POST /new/2022-05-27-FSR HTTP/1.1");
+        assert_eq!(body, "# Protokoll");
+    }
+
+    #[test]
+    fn create_existing_note() {
+        let (url, handle) = stub_server("409 Conflict");
+
+        let created = super::create_note(&Client::new(), &url, "2022-05-27-FSR", "").unwrap();
+
+        handle.join().unwrap();
+
+        assert!(!created);
+    }
+
+    #[test]
+    fn create_note_fails() {
+        let (url, handle) = stub_server("403 Forbidden");
+
+        let result = super::create_note(&Client::new(), &url, "2022-05-27-FSR", "");
+
+        handle.join().unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn prepare_note_retries_announcement() {
+        let (url, handle) = stub_responses(&[
+            ("201 Created", ""),
+            ("500 Internal Server Error", ""),
+            ("204 No Content", ""),
+        ]);
+        let webhook_url = url.join("webhooks/123").unwrap();
+        let announcement = Some((&webhook_url, "Das Pad ist bereit"));
+        let mut state = None;
+
+        let first = super::prepare_note(
+            &Client::new(),
+            &url,
+            "2022-05-27-FSR",
+            || Ok("# Protokoll".to_string()),
+            announcement,
+            &mut state,
+        );
+
+        assert!(first.is_err());
+        assert_eq!(state, Some(NoteState::Created));
+
+        super::prepare_note(
+            &Client::new(),
+            &url,
+            "2022-05-27-FSR",
+            || panic!("the note must not be rendered again"),
+            announcement,
+            &mut state,
+        )
+        .unwrap();
+
+        let requests = handle.join().unwrap();
+        let request_lines: Vec<&str> = requests.iter().map(|r| r.request_line.as_str()).collect();
+
+        assert_eq!(state, Some(NoteState::Announced));
+        assert_eq!(
+            request_lines,
+            vec![
+                "POST /new/2022-05-27-FSR HTTP/1.1",
+                "POST /webhooks/123 HTTP/1.1",
+                "POST /webhooks/123 HTTP/1.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn prepare_existing_note() {
+        let (url, handle) = stub_responses(&[("409 Conflict", "")]);
+        let webhook_url = url.join("webhooks/123").unwrap();
+        let mut state = None;
+
+        super::prepare_note(
+            &Client::new(),
+            &url,
+            "2022-05-27-FSR",
+            || Ok(String::new()),
+            Some((&webhook_url, "Das Pad ist bereit")),
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(handle.join().unwrap().len(), 1);
+        assert_eq!(state, Some(NoteState::Existed));
+    }
+}
@@ -12,7 +12,7 @@ pub struct PostTemplate {
     date_machine: String,
 }
 
-fn find_content_dir(root: &Path) -> PathBuf {
+pub fn find_content_dir(root: &Path) -> PathBuf {
     root.join("content")
 }
 
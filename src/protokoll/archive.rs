@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+
+use super::parse::{self, ParsedProtokoll};
+use crate::{post, SitzungTyp};
+
+/// the directory below the content dir, in which all protokolle are stored
+pub const PROTOKOLL_DIR: &str = "protokolle";
+
+/// lists all protokolle below 'content/protokolle', sorted by their path.
+/// section pages like '_index.de.md' are skipped.
+pub fn find_protokolle(root: &Path) -> Result<Vec<PathBuf>> {
+    let protokoll_dir = post::find_content_dir(root).join(PROTOKOLL_DIR);
+
+    if !protokoll_dir.exists() {
+        bail!("protokoll dir doesnt exist yet");
+    }
+
+    let mut result = Vec::new();
+    collect_markdown_files(&protokoll_dir, &mut result)?;
+
+    result.sort();
+
+    Ok(result)
+}
+
+/// finds the latest protokoll of a regular sitzung before `date`. protokolle which
+/// cant be parsed are skipped.
+pub fn find_previous_protokoll(root: &Path, date: NaiveDate) -> Result<Option<ParsedProtokoll>> {
+    let mut result: Option<ParsedProtokoll> = None;
+
+    for path in find_protokolle(root)? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+        let Ok(protokoll) = parse::parse(&content) else {
+            continue;
+        };
+
+        let is_regular = matches!(
+            protokoll.sitzung_kind,
+            SitzungTyp::Normal | SitzungTyp::Ersatz | SitzungTyp::Dringlichkeit
+        );
+        let is_newer = result.as_ref().is_none_or(|r| protokoll.date > r.date);
+
+        if is_regular && protokoll.date < date && is_newer {
+            result = Some(protokoll);
+        }
+    }
+
+    Ok(result)
+}
+
+fn collect_markdown_files(dir: &Path, result: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("unable to read directory '{}'", dir.to_string_lossy()))?;
+
+    for entry in entries {
+        let path = entry.context("unable to read directory entry")?.path();
+
+        if path.is_dir() {
+            collect_markdown_files(&path, result)?;
+            continue;
+        }
+
+        let is_markdown = path.extension().is_some_and(|e| e == "md");
+        let is_section = path
+            .file_name()
+            .is_some_and(|e| e.to_string_lossy().starts_with("_index"));
+
+        if is_markdown && !is_section {
+            result.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    use std::fs;
+
+    #[test]
+    fn protokoll_dir_doesnt_exist() {
+        let tmpdir = tempdir().unwrap();
+
+        let result = super::find_protokolle(tmpdir.path());
+
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn find_protokolle() {
+        let tmpdir = tempdir().unwrap();
+        let protokoll_dir = tmpdir.path().join("content/protokolle");
+
+        fs::create_dir_all(protokoll_dir.join("2021")).unwrap();
+        fs::create_dir_all(protokoll_dir.join("2022")).unwrap();
+
+        fs::write(protokoll_dir.join("2022/05-27-protokoll.de.md"), "").unwrap();
+        fs::write(protokoll_dir.join("2021/12-01-vv-protokoll.de.md"), "").unwrap();
+        fs::write(protokoll_dir.join("2022/_index.de.md"), "").unwrap();
+        fs::write(protokoll_dir.join("2022/notes.txt"), "").unwrap();
+
+        let result = super::find_protokolle(tmpdir.path()).unwrap();
+
+        let expected = vec![
+            protokoll_dir.join("2021/12-01-vv-protokoll.de.md"),
+            protokoll_dir.join("2022/05-27-protokoll.de.md"),
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn find_previous_protokoll() {
+        let tmpdir = tempdir().unwrap();
+        let protokoll_dir = tmpdir.path().join("content/protokolle/2022");
+
+        fs::create_dir_all(&protokoll_dir).unwrap();
+
+        let protokoll = |date: &str, kind: &str, rat: &str| {
+            format!(
+                "---\ndate: \"{}\"\nsitzung-kind: \"{}\"\n---\n\n#### Anwesende Rätys\n- {}\n",
+                date, kind, rat
+            )
+        };
+
+        fs::write(
+            protokoll_dir.join("05-13-protokoll.de.md"),
+            protokoll("2022-05-13", "normal", "Elif"),
+        )
+        .unwrap();
+        fs::write(
+            protokoll_dir.join("05-20-protokoll.de.md"),
+            protokoll("2022-05-20", "normal", "Valentin"),
+        )
+        .unwrap();
+        fs::write(
+            protokoll_dir.join("05-24-vv-protokoll.de.md"),
+            protokoll("2022-05-24", "vv", "Marcel"),
+        )
+        .unwrap();
+        fs::write(
+            protokoll_dir.join("05-27-protokoll.de.md"),
+            protokoll("2022-05-27", "normal", "Jonas"),
+        )
+        .unwrap();
+        fs::write(protokoll_dir.join("broken.de.md"), "no frontmatter").unwrap();
+
+        let previous = |y, m, d| {
+            super::find_previous_protokoll(tmpdir.path(), NaiveDate::from_ymd_opt(y, m, d).unwrap())
+                .unwrap()
+        };
+
+        let result = previous(2022, 5, 27).unwrap();
+
+        assert_eq!(result.date, NaiveDate::from_ymd_opt(2022, 5, 20).unwrap());
+        assert_eq!(result.anwesende, vec!["Valentin"]);
+
+        assert!(previous(2022, 5, 1).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use serde::{de, Deserialize, Deserializer};
+
+/// an amount of money in euro cents
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Betrag(pub i64);
+
+impl Betrag {
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for Betrag {
+    /// formats the amount the german way, e.g. "1.250,00 €"
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let euros = (self.0 / 100).unsigned_abs().to_string();
+        let cents = (self.0 % 100).unsigned_abs();
+
+        let mut grouped = String::new();
+
+        for (index, digit) in euros.chars().enumerate() {
+            if index > 0 && (euros.len() - index).is_multiple_of(3) {
+                grouped.push('.');
+            }
+
+            grouped.push(digit);
+        }
+
+        // pad, so that amounts can be aligned in tables
+        f.pad(&format!("{}{},{:02} €", sign, grouped, cents))
+    }
+}
+
+impl FromStr for Betrag {
+    type Err = anyhow::Error;
+
+    /// parses amounts like "250", "250.5", "250,50 €" or "1.250,00"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim().trim_end_matches('€').trim_end().replace(' ', "");
+
+        // if there is a comma, its the decimal separator and dots group thousands
+        let normalized = if trimmed.contains(',') {
+            trimmed.replace('.', "").replace(',', ".")
+        } else {
+            trimmed
+        };
+
+        let (sign, unsigned) = normalized
+            .strip_prefix('-')
+            .map_or((1, normalized.as_str()), |rest| (-1, rest));
+
+        let (euro_part, cent_part) = unsigned.split_once('.').unwrap_or((unsigned, "0"));
+
+        // the sign was handled above, so both parts may only consist of digits
+        let is_number = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+        if euro_part.is_empty()
+            || cent_part.len() > 2
+            || !is_number(euro_part)
+            || !is_number(cent_part)
+        {
+            bail!("invalid amount '{}'", s);
+        }
+
+        let euros: i64 = euro_part
+            .parse()
+            .with_context(|| format!("invalid amount '{}'", s))?;
+        let cents: i64 = format!("{:0<2}", cent_part)
+            .parse()
+            .with_context(|| format!("invalid amount '{}'", s))?;
+
+        let total = euros
+            .checked_mul(100)
+            .and_then(|e| e.checked_add(cents))
+            .ok_or_else(|| anyhow!("amount '{}' is too large", s))?;
+
+        Ok(Self(sign * total))
+    }
+}
+
+impl<'de> Deserialize<'de> for Betrag {
+    /// accepts numbers in euro as well as strings in any format `from_str` understands
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Integer(i64),
+            Float(f64),
+            String(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Integer(euros) => euros
+                .checked_mul(100)
+                .map(Self)
+                .ok_or_else(|| de::Error::custom(format!("amount '{}' is too large", euros))),
+            Raw::Float(euros) => {
+                let cents = (euros * 100.0).round();
+
+                // i64::MAX isnt representable as a float, so it rounds up to 2^63
+                if !cents.is_finite() || cents < i64::MIN as f64 || cents >= i64::MAX as f64 {
+                    return Err(de::Error::custom(format!("invalid amount '{}'", euros)));
+                }
+
+                Ok(Self(cents as i64))
+            }
+            Raw::String(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+impl Add for Betrag {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Betrag {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Sum for Betrag {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::Betrag;
+
+    #[test]
+    fn display() {
+        assert_eq!(Betrag(25000).to_string(), "250,00 €");
+        assert_eq!(Betrag(125_050).to_string(), "1.250,50 €");
+        assert_eq!(Betrag(5).to_string(), "0,05 €");
+        assert_eq!(Betrag(-123_456_789).to_string(), "-1.234.567,89 €");
+        assert_eq!(format!("{:>10}", Betrag(25000)), "  250,00 €");
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("250".parse::<Betrag>().unwrap(), Betrag(25000));
+        assert_eq!("250.5".parse::<Betrag>().unwrap(), Betrag(25050));
+        assert_eq!("250,50 €".parse::<Betrag>().unwrap(), Betrag(25050));
+        assert_eq!("1.250,00".parse::<Betrag>().unwrap(), Betrag(125_000));
+        assert_eq!("-3,10€".parse::<Betrag>().unwrap(), Betrag(-310));
+
+        assert!("zwei euro".parse::<Betrag>().is_err());
+        assert!("2,001".parse::<Betrag>().is_err());
+        assert!("--5".parse::<Betrag>().is_err());
+        assert!("5,-5".parse::<Betrag>().is_err());
+        assert!("+5".parse::<Betrag>().is_err());
+    }
+
+    #[test]
+    fn deserialize() {
+        let result: Vec<Betrag> = serde_json::from_str(r#"[250, 12.99, "1.250,00"]"#).unwrap();
+
+        assert_eq!(result, vec![Betrag(25000), Betrag(1299), Betrag(125_000)]);
+
+        assert!(serde_json::from_str::<Betrag>("92233720368547759").is_err());
+        assert!(serde_json::from_str::<Betrag>("1e300").is_err());
+        assert!(toml::from_str::<BTreeMap<String, Betrag>>("betrag = nan").is_err());
+        assert!(toml::from_str::<BTreeMap<String, Betrag>>("betrag = -inf").is_err());
+    }
+}
@@ -1,30 +1,313 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, FixedOffset};
-use reqwest::blocking::Client;
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
 use serde::Deserialize;
-use url::Url;
 
-#[derive(Debug, Deserialize)]
+use crate::api::ApiClient;
+
+/// the start or end of an event. all-day events only carry a date.
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(untagged)]
+pub enum EventTime {
+    DateTime(DateTime<FixedOffset>),
+    Date(NaiveDate),
+}
+
+impl EventTime {
+    pub fn date_naive(&self) -> NaiveDate {
+        match self {
+            Self::DateTime(datetime) => datetime.date_naive(),
+            Self::Date(date) => *date,
+        }
+    }
+
+    pub fn time(&self) -> Option<NaiveTime> {
+        match self {
+            Self::DateTime(datetime) => Some(datetime.time()),
+            Self::Date(_) => None,
+        }
+    }
+
+    pub fn with_timezone<Tz: TimeZone>(&self, tz: &Tz) -> Self {
+        match self {
+            Self::DateTime(datetime) => Self::DateTime(datetime.with_timezone(tz).fixed_offset()),
+            Self::Date(date) => Self::Date(*date),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
 pub struct Event {
     #[serde(rename = "summary")]
     pub title: Option<String>,
     pub location: Option<String>,
-    pub start: DateTime<FixedOffset>,
+    pub start: EventTime,
+    /// for all-day events the end date is exclusive, as in iCalendar
+    #[serde(default)]
+    pub end: Option<EventTime>,
 }
 
-pub fn fetch_calendar_events(api_url: &Url, client: &Client) -> Result<Vec<Event>> {
-    let endpoint = api_url.join("api/calendar/events/")?;
+impl Event {
+    /// the last day on which this event takes place.
+    ///
+    /// An event ending exactly at midnight ends on the day before.
+    pub fn last_day(&self) -> NaiveDate {
+        let start = self.start.date_naive();
+
+        let last_day = match self.end {
+            Some(EventTime::DateTime(end)) if end.time() == NaiveTime::MIN => {
+                let end_date = end.date_naive();
+                end_date.pred_opt().unwrap_or(end_date)
+            }
+            Some(EventTime::DateTime(end)) => end.date_naive(),
+            Some(EventTime::Date(end)) => end.pred_opt().unwrap_or(end),
+            None => start,
+        };
+
+        last_day.max(start)
+    }
+
+    pub fn is_multi_day(&self) -> bool {
+        self.last_day() > self.start.date_naive()
+    }
+}
+
+/// which events are shown under "Anstehende Veranstaltungen"
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct EventFilter {
+    /// only show events starting within this many days after the sitzung
+    pub days: Option<u64>,
+    /// hide events whose title contains one of these (case insensitive)
+    pub exclude_titles: Vec<String>,
+    /// hide events whose location contains one of these (case insensitive)
+    pub exclude_locations: Vec<String>,
+    /// show every occurrence of recurring events, instead of only the next one
+    pub keep_recurring: bool,
+}
+
+impl Default for EventFilter {
+    fn default() -> Self {
+        Self {
+            days: Some(14),
+            exclude_titles: vec![],
+            exclude_locations: vec![],
+            keep_recurring: false,
+        }
+    }
+}
+
+fn contains_any(value: Option<&String>, patterns: &[String]) -> bool {
+    let Some(lowercase) = value.map(|v| v.to_lowercase()) else {
+        return false;
+    };
+
+    patterns
+        .iter()
+        .any(|p| lowercase.contains(&p.to_lowercase()))
+}
+
+/// drops all events which already ended before the day of the sitzung or are
+/// excluded by `filter`, and sorts the remaining ones by their start
+pub fn filter_events(
+    events: Vec<Event>,
+    sitzung_date: DateTime<FixedOffset>,
+    filter: &EventFilter,
+) -> Vec<Event> {
+    let day_start = sitzung_date.date_naive();
+    let window_end = filter
+        .days
+        .and_then(|days| day_start.checked_add_days(Days::new(days)));
+
+    let mut result: Vec<Event> = events
+        .into_iter()
+        .filter(|e| e.last_day() >= day_start)
+        .filter(|e| window_end.is_none_or(|end| e.start.date_naive() <= end))
+        .filter(|e| !contains_any(e.title.as_ref(), &filter.exclude_titles))
+        .filter(|e| !contains_any(e.location.as_ref(), &filter.exclude_locations))
+        .collect();
+
+    result.sort_by_key(|e| (e.start.date_naive(), e.start.time()));
+
+    if !filter.keep_recurring {
+        let mut seen: Vec<(Option<String>, Option<String>)> = vec![];
+
+        result.retain(|e| {
+            let key = (e.title.clone(), e.location.clone());
+
+            if seen.contains(&key) {
+                false
+            } else {
+                seen.push(key);
+                true
+            }
+        });
+    }
+
+    result
+}
+
+pub fn fetch_calendar_events(api: &ApiClient) -> Result<Vec<Event>> {
+    api.get_json("api/calendar/events/", &[])
+        .context("unable to fetch events")
+}
+
+/// fetches the events to list in the protokoll of the sitzung at `sitzung_date`
+pub fn fetch_upcoming_events(
+    api: &ApiClient,
+    sitzung_date: DateTime<FixedOffset>,
+    filter: &EventFilter,
+    tz: &Tz,
+) -> Result<Vec<Event>> {
+    let events = fetch_calendar_events(api)?;
+
+    Ok(upcoming_events(events, sitzung_date, filter, tz))
+}
+
+/// the `events` to list in the protokoll of the sitzung at `sitzung_date`. their times
+/// are converted to `tz`.
+pub fn upcoming_events(
+    events: Vec<Event>,
+    sitzung_date: DateTime<FixedOffset>,
+    filter: &EventFilter,
+    tz: &Tz,
+) -> Vec<Event> {
+    let local_events = events
+        .into_iter()
+        .map(|e| Event {
+            title: e.title,
+            location: e.location,
+            start: e.start.with_timezone(tz),
+            end: e.end.map(|end| end.with_timezone(tz)),
+        })
+        .collect();
+
+    filter_events(local_events, sitzung_date, filter)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveDate};
+    use pretty_assertions::assert_eq;
+
+    use super::{Event, EventFilter, EventTime};
+
+    fn datetime(month: u32, day: u32) -> DateTime<FixedOffset> {
+        NaiveDate::from_ymd_opt(2022, month, day)
+            .unwrap()
+            .and_hms_opt(19, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(2 * 60 * 60).unwrap())
+            .unwrap()
+    }
+
+    fn event(title: &str, location: &str, month: u32, day: u32) -> Event {
+        Event {
+            title: Some(title.to_string()),
+            location: Some(location.to_string()),
+            start: EventTime::DateTime(datetime(month, day)),
+            end: None,
+        }
+    }
+
+    #[test]
+    fn filter_events() {
+        let events = vec![
+            event("Spieleabend", "25.12.O2.35", 6, 8),
+            event("Grillen", "Wiese", 5, 20),
+            event("Spieleabend", "25.12.O2.35", 6, 1),
+            event("Sommerfest", "Mensa", 8, 1),
+            event("Interne Klausur", "Zoom", 5, 30),
+            event("Kochabend", "25.12.O2.35", 5, 27),
+            Event {
+                title: Some("Ersti-Fahrt".to_string()),
+                location: None,
+                start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 25).unwrap()),
+                end: Some(EventTime::Date(
+                    NaiveDate::from_ymd_opt(2022, 5, 28).unwrap(),
+                )),
+            },
+            Event {
+                title: Some("Tag der offenen Tür".to_string()),
+                location: None,
+                start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 26).unwrap()),
+                end: Some(EventTime::Date(
+                    NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+                )),
+            },
+        ];
+
+        let filter = EventFilter {
+            exclude_titles: vec!["intern".to_string()],
+            ..EventFilter::default()
+        };
+
+        let result = super::filter_events(events, datetime(5, 27), &filter);
+
+        let expected = vec![
+            Event {
+                title: Some("Ersti-Fahrt".to_string()),
+                location: None,
+                start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 25).unwrap()),
+                end: Some(EventTime::Date(
+                    NaiveDate::from_ymd_opt(2022, 5, 28).unwrap(),
+                )),
+            },
+            event("Kochabend", "25.12.O2.35", 5, 27),
+            event("Spieleabend", "25.12.O2.35", 6, 1),
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn filter_events_keep_recurring() {
+        let events = vec![
+            event("Spieleabend", "25.12.O2.35", 6, 8),
+            event("Spieleabend", "25.12.O2.35", 6, 1),
+            event("Spieleabend", "25.12.O2.35", 8, 1),
+        ];
+
+        let filter = EventFilter {
+            days: None,
+            exclude_locations: vec!["zoom".to_string()],
+            keep_recurring: true,
+            ..EventFilter::default()
+        };
+
+        let result = super::filter_events(events, datetime(5, 27), &filter);
+
+        let expected = vec![
+            event("Spieleabend", "25.12.O2.35", 6, 1),
+            event("Spieleabend", "25.12.O2.35", 6, 8),
+            event("Spieleabend", "25.12.O2.35", 8, 1),
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn deserialize_events() {
+        let json = r#"[
+            { "summary": "Spieleabend", "location": null, "start": "2022-05-30T19:00:00+02:00" },
+            { "summary": "Ersti-Fahrt", "location": "Jugendherberge", "start": "2022-05-12", "end": "2022-05-15" }
+        ]"#;
 
-    let response = client
-        .get(endpoint)
-        .send()
-        .context("unable to fetch events")?
-        .error_for_status()
-        .context("unable to fetch events")?;
+        let events: Vec<Event> = serde_json::from_str(json).unwrap();
 
-    let events = response
-        .json()
-        .context("failed to deserialize events".to_string())?;
+        assert_eq!(events[0].start, EventTime::DateTime(datetime(5, 30)));
+        assert_eq!(events[0].end, None);
+        assert!(!events[0].is_multi_day());
 
-    Ok(events)
+        assert_eq!(
+            events[1].start,
+            EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 12).unwrap())
+        );
+        assert_eq!(
+            events[1].last_day(),
+            NaiveDate::from_ymd_opt(2022, 5, 14).unwrap()
+        );
+        assert!(events[1].is_multi_day());
+    }
 }
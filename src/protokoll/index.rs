@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use askama::Template;
+use chrono::NaiveDate;
+
+use super::{find_frontmatter, parse, parse_protokoll};
+use crate::SitzungTyp;
+
+/// the headings every protokoll contains, which are left out of the index
+const STANDARD_TOPS: &[&str] = &["Regularia", "Berichte, Mail und Post", "Verschiedenes"];
+
+/// marks index pages written by prototool, which may be overwritten without asking
+pub const GENERATED_MARKER: &str = "<!-- generated by prototool index -->";
+
+/// a sitzung as listed on the index page of its year
+#[derive(Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// the file name without language and extension, e.g. '05-27-protokoll'
+    pub name: String,
+    pub date: NaiveDate,
+    pub sitzung_kind: SitzungTyp,
+    /// the titles of all tops, except the ones every protokoll has
+    pub tops: Vec<String>,
+    /// the number of accepted votes, without those on the dringlichkeit of an antrag
+    pub beschluesse: usize,
+}
+
+impl IndexEntry {
+    /// parses the protokoll at `path` with the language `lang`. drafts arent listed and
+    /// result in `None`.
+    pub fn from_protokoll(path: &Path, lang: &str, content: &str) -> Result<Option<Self>> {
+        let frontmatter = find_frontmatter(&parse_protokoll(content)?)?;
+
+        if frontmatter.draft == Some(true) {
+            return Ok(None);
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let name = file_name
+            .strip_suffix(&format!(".{}.md", lang))
+            .ok_or_else(|| anyhow!("'{}' isnt written in '{}'", file_name, lang))?;
+
+        let protokoll = parse::parse(content)?;
+        let beschluesse = protokoll
+            .beschluesse()
+            .filter(|a| !a.is_dringlichkeit())
+            .count();
+
+        Ok(Some(Self {
+            name: name.to_string(),
+            date: protokoll.date,
+            sitzung_kind: protokoll.sitzung_kind,
+            tops: protokoll
+                .tops
+                .iter()
+                .map(|t| t.title.clone())
+                .filter(|t| !STANDARD_TOPS.contains(&t.as_str()))
+                .collect(),
+            beschluesse,
+        }))
+    }
+}
+
+#[derive(Debug, Template)]
+#[template(path = "../templates/protokoll-index.md")]
+pub struct IndexTemplate {
+    pub year: String,
+    pub entries: Vec<IndexEntry>,
+    pub marker: &'static str,
+}
+
+/// renders the index page of `year`, listing `entries` by their date
+pub fn render_index(year: &str, mut entries: Vec<IndexEntry>) -> Result<String> {
+    entries.sort_by_key(|e| e.date);
+
+    let template = IndexTemplate {
+        year: year.to_string(),
+        entries,
+        marker: GENERATED_MARKER,
+    };
+
+    template.render().context("failed to render index template")
+}
+
+/// whether the index page `content` was generated by prototool
+pub fn is_generated(content: &str) -> bool {
+    content.contains(GENERATED_MARKER)
+}
+
+mod filters {
+    /// escapes `text` to be used within a markdown table cell
+    pub fn cell<T: std::fmt::Display>(text: T, _: &dyn askama::Values) -> askama::Result<String> {
+        Ok(text.to_string().replace('|', "\\|"))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::IndexEntry;
+    use crate::SitzungTyp;
+
+    static PROTOKOLL_WITH_TOPS: &str = include_str!("../../tests/protokoll-with-tops.md");
+    static PROTOKOLL_INDEX: &str = include_str!("../../tests/protokoll-index.md");
+
+    fn entry(name: &str, date: NaiveDate, tops: &[&str], beschluesse: usize) -> IndexEntry {
+        IndexEntry {
+            name: name.to_string(),
+            date,
+            sitzung_kind: SitzungTyp::Normal,
+            tops: tops.iter().map(|t| t.to_string()).collect(),
+            beschluesse,
+        }
+    }
+
+    #[test]
+    fn from_protokoll() {
+        // only the first vote was filled in, the others are still placeholders
+        let published = PROTOKOLL_WITH_TOPS
+            .replace("draft: true", "draft: false")
+            .replacen(
+                "n Zustimmen, m Gegenstimmen, k Enthaltungen",
+                "7 Zustimmen, 0 Gegenstimmen, 1 Enthaltungen",
+                1,
+            );
+        let path = Path::new("content/protokolle/2022/05-27-protokoll.de.md");
+
+        let result = IndexEntry::from_protokoll(path, "de", &published).unwrap();
+
+        assert_eq!(
+            result,
+            Some(entry(
+                "05-27-protokoll",
+                NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+                &["Blumen für Valentin", "Volt Zapfanlage"],
+                1
+            ))
+        );
+        assert!(IndexEntry::from_protokoll(path, "en", &published).is_err());
+    }
+
+    #[test]
+    fn drafts_arent_listed() {
+        let path = Path::new("content/protokolle/2022/05-27-protokoll.de.md");
+
+        let result = IndexEntry::from_protokoll(path, "de", PROTOKOLL_WITH_TOPS).unwrap();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn render_index() {
+        let mut vv = entry(
+            "06-01-vv-protokoll",
+            NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(),
+            &[],
+            0,
+        );
+        vv.sitzung_kind = SitzungTyp::VV;
+
+        let entries = vec![
+            vv,
+            entry(
+                "05-27-protokoll",
+                NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+                &["Blumen für Valentin", "Volt | Zapfanlage"],
+                2,
+            ),
+        ];
+
+        let result = super::render_index("2022", entries).unwrap();
+
+        assert_eq!(result, PROTOKOLL_INDEX);
+        assert!(super::is_generated(&result));
+    }
+}
@@ -4,18 +4,42 @@ use chrono::NaiveDate;
 use markdown::mdast;
 use serde::Deserialize;
 
-use crate::{Event, PersonWithAbmeldung, Sitzung, SitzungTyp};
+use self::{events::EventFilter, parse::ParsedProtokoll, person::Abmeldung};
+use crate::{config::Config, Event, Person, PersonWithAbmeldung, Sitzung, SitzungTyp};
 
+pub mod archive;
+pub mod betrag;
 pub mod events;
+pub mod index;
+pub mod parse;
 pub mod person;
+pub mod publish;
+pub mod search;
 pub mod sitzung;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ProtokollFrontmatter {
+    pub title: Option<String>,
     pub date: Option<NaiveDate>,
     pub lastmod: Option<NaiveDate>,
     pub sitzung_kind: Option<SitzungTyp>,
+    pub draft: Option<bool>,
+    #[serde(rename = "hiddenUntil")]
+    pub hidden_until: Option<NaiveDate>,
+}
+
+/// parses a protokoll into its mdast, including its frontmatter
+pub fn parse_protokoll(content: &str) -> Result<mdast::Node> {
+    let markdown_opts = markdown::ParseOptions {
+        constructs: markdown::Constructs {
+            frontmatter: true,
+            ..markdown::Constructs::gfm()
+        },
+        ..markdown::ParseOptions::default()
+    };
+
+    markdown::to_mdast(content, &markdown_opts).map_err(|_| anyhow!("unable to parse protokoll"))
 }
 
 pub fn find_frontmatter(protokoll: &mdast::Node) -> Result<ProtokollFrontmatter> {
@@ -54,12 +78,57 @@ pub fn find_protokoll_date(frontmatter: &ProtokollFrontmatter) -> Result<NaiveDa
 pub struct ProtokollTemplate {
     pub sitzung: Sitzung,
     pub raete: Vec<PersonWithAbmeldung>,
+    /// present persons, which arent räte
+    pub gaeste: Vec<Person>,
+    pub redeleitung: Option<Person>,
+    pub protokoll: Option<Person>,
     pub events: Vec<Event>,
+    /// open todos of the previous protokoll
+    pub todos: Vec<parse::Todo>,
+}
+
+impl ProtokollTemplate {
+    /// assembles the template from what was fetched from the backend. the sitzung,
+    /// abmeldungen and events are converted to the configured timezone and the open
+    /// todos are carried over from `previous`. all räte start out as absent and
+    /// there are no guests, redeleitung or protokoll yet.
+    pub fn assemble(
+        config: &Config,
+        mut sitzung: Sitzung,
+        raete: &[Person],
+        mut abmeldungen: Vec<Abmeldung>,
+        calendar_events: Vec<Event>,
+        event_filter: &EventFilter,
+        previous: Option<&ParsedProtokoll>,
+    ) -> Self {
+        let tz = &config.timezone;
+
+        sitzung.to_timezone(tz);
+        sitzung.fill_antragsfrist(config.antraege.antragsfrist_days, tz);
+
+        for abmeldung in &mut abmeldungen {
+            abmeldung.to_timezone(tz);
+        }
+
+        let events = events::upcoming_events(calendar_events, sitzung.datetime, event_filter, tz);
+
+        Self {
+            raete: person::determine_abgemeldet_räte(raete, &abmeldungen),
+            sitzung,
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            events,
+            todos: previous
+                .map(|p| p.open_todos().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 // these are functions available within the template
 mod filters {
-    use chrono::{DateTime, Days, FixedOffset, NaiveDate};
+    use chrono::{DateTime, Datelike, Days, FixedOffset, NaiveDate};
 
     use crate::{Antrag, Event, PersonWithAbmeldung, Sitzung, SitzungTyp, Top, TopTyp};
 
@@ -67,7 +136,77 @@ mod filters {
         tops: &'a [Top],
         _: &dyn askama::Values,
     ) -> askama::Result<Vec<&'a Top>> {
-        let result = tops.iter().filter(|e| e.typ == TopTyp::Normal).collect();
+        Ok(tops_of_typ(tops, TopTyp::Normal))
+    }
+
+    fn tops_of_typ(tops: &[Top], typ: TopTyp) -> Vec<&Top> {
+        let mut result: Vec<&Top> = tops.iter().filter(|e| e.typ == typ).collect();
+
+        result.sort_by_key(|e| e.weight);
+
+        result
+    }
+
+    pub fn regularia_tops<'a>(
+        tops: &'a [Top],
+        _: &dyn askama::Values,
+    ) -> askama::Result<Vec<&'a Top>> {
+        Ok(tops_of_typ(tops, TopTyp::Regularia))
+    }
+
+    pub fn bericht_tops<'a>(
+        tops: &'a [Top],
+        _: &dyn askama::Values,
+    ) -> askama::Result<Vec<&'a Top>> {
+        Ok(tops_of_typ(tops, TopTyp::Bericht))
+    }
+
+    pub fn verschiedenes_tops<'a>(
+        tops: &'a [Top],
+        _: &dyn askama::Values,
+    ) -> askama::Result<Vec<&'a Top>> {
+        Ok(tops_of_typ(tops, TopTyp::Verschiedenes))
+    }
+
+    /// the submitters and financial details of an antrag, one per line
+    pub fn antrag_metadata(antrag: &Antrag, _: &dyn askama::Values) -> askama::Result<String> {
+        let mut lines = vec![];
+
+        if !antrag.antragsteller.is_empty() {
+            let names: Vec<&str> = antrag
+                .antragsteller
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect();
+
+            lines.push(format!("Antragsteller:in: {}", names.join(", ")));
+        }
+
+        if let Some(betrag) = antrag.betrag {
+            lines.push(format!("Betrag: {}", betrag));
+        }
+
+        if let Some(haushaltstitel) = &antrag.haushaltstitel {
+            lines.push(format!("Haushaltstitel: {}", haushaltstitel));
+        }
+
+        // trailing double spaces are markdown line breaks
+        Ok(lines.join("  \n"))
+    }
+
+    /// the id hugo assigns to the heading of this antrag, so that it can be linked to
+    pub fn antrag_anchor(antrag: &Antrag, _: &dyn askama::Values) -> askama::Result<String> {
+        let heading = format!("Antrag: {}", antrag.titel).to_lowercase();
+
+        let result = heading
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                '-' | '_' => Some(c),
+                _ if c.is_alphanumeric() => Some(c),
+                _ => None,
+            })
+            .collect();
 
         Ok(result)
     }
@@ -83,15 +222,33 @@ mod filters {
     }
 
     pub fn event_format(event: &Event, _: &dyn askama::Values) -> askama::Result<String> {
-        let result = format!(
-            "{} {} {} Uhr {}",
-            event.start.format("%d.%m."),
-            event.title.as_ref().map_or("", |e| e.as_str()),
-            event.start.format("%H:%M"),
-            event.location.as_ref().map_or("", |e| e.as_str()),
-        );
+        let start = event.start.date_naive();
+        let last_day = event.last_day();
 
-        Ok(result)
+        let date = if !event.is_multi_day() {
+            start.format("%d.%m.").to_string()
+        } else if (start.year(), start.month()) == (last_day.year(), last_day.month()) {
+            format!("{}–{}", start.format("%d."), last_day.format("%d.%m."))
+        } else {
+            format!("{}–{}", start.format("%d.%m."), last_day.format("%d.%m."))
+        };
+
+        let mut parts = vec![date];
+
+        if let Some(title) = &event.title {
+            parts.push(title.to_string());
+        }
+
+        // the time of multi day events is rarely interesting
+        if let (Some(time), false) = (event.start.time(), event.is_multi_day()) {
+            parts.push(time.format("%H:%M Uhr").to_string());
+        }
+
+        if let Some(location) = &event.location {
+            parts.push(location.to_string());
+        }
+
+        Ok(parts.join(" "))
     }
 
     pub fn protokoll_title(sitzung: &Sitzung, _: &dyn askama::Values) -> askama::Result<String> {
@@ -106,6 +263,34 @@ mod filters {
         Ok(result)
     }
 
+    /// e.g. "Valentin, entschuldigt (ab 19 Uhr)" for räte, which are only abgemeldet
+    /// for a part of the sitzung
+    pub fn anwesend_label(
+        rat: &PersonWithAbmeldung,
+        _: &dyn askama::Values,
+    ) -> askama::Result<String> {
+        let details = rat
+            .abmeldung
+            .as_ref()
+            .filter(|a| a.is_partial())
+            .and_then(|a| a.details());
+
+        Ok(details.map_or_else(
+            || rat.to_string(),
+            |d| format!("{}, entschuldigt ({})", rat, d),
+        ))
+    }
+
+    /// e.g. "Elif (Krankheit)"
+    pub fn entschuldigt_label(
+        rat: &PersonWithAbmeldung,
+        _: &dyn askama::Values,
+    ) -> askama::Result<String> {
+        let details = rat.abmeldung.as_ref().and_then(|a| a.details());
+
+        Ok(details.map_or_else(|| rat.to_string(), |d| format!("{} ({})", rat, d)))
+    }
+
     pub fn anwesende_raete_label(
         raete: &[PersonWithAbmeldung],
         _: &dyn askama::Values,
@@ -161,9 +346,8 @@ mod filters {
         let result = sitzung
             .tops
             .iter()
-            .map(|top| &top.antraege)
-            .flatten()
-            .filter(|antrag| antrag.erstellt_am > sitzung.antragsfrist)
+            .flat_map(|top| &top.antraege)
+            .filter(|antrag| sitzung.is_late(antrag))
             .collect();
 
         Ok(result)
@@ -174,8 +358,11 @@ mod filters {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::{
-        person::PersonWithAbmeldung,
-        sitzung::{Antrag, Sitzung, SitzungTyp, Top, TopTyp},
+        betrag::Betrag,
+        events::{Event, EventTime},
+        parse::Todo,
+        person::{Abmeldung, AbmeldungGrund, Person, PersonWithAbmeldung},
+        sitzung::{Anhang, Antrag, Sitzung, SitzungTyp, Top, TopTyp},
     };
 
     use super::ProtokollTemplate;
@@ -187,7 +374,15 @@ mod tests {
     static PROTOKOLL_NO_TOPS: &str = include_str!("../../tests/protokoll-no-tops.md");
     static PROTOKOLL_VV: &str = include_str!("../../tests/protokoll-vv.md");
     static PROTOKOLL_WITH_TOPS: &str = include_str!("../../tests/protokoll-with-tops.md");
+    static PROTOKOLL_WITH_OTHER_TOPS: &str =
+        include_str!("../../tests/protokoll-with-other-tops.md");
+    static PROTOKOLL_WITH_ANTRAG_METADATA: &str =
+        include_str!("../../tests/protokoll-with-antrag-metadata.md");
     static PROTOKOLL_WITH_RÄTE: &str = include_str!("../../tests/protokoll-with-rate.md");
+    static PROTOKOLL_WITH_PARTICIPANTS: &str =
+        include_str!("../../tests/protokoll-with-participants.md");
+    static PROTOKOLL_WITH_ABMELDUNGEN: &str =
+        include_str!("../../tests/protokoll-with-abmeldungen.md");
     static PROTOKOLL_WITH_RÄTE_NO_BESCHLUSS: &str =
         include_str!("../../tests/protokoll-with-rate-no-beschluss.md");
 
@@ -195,6 +390,15 @@ mod tests {
         FixedOffset::east_opt(3 * 60 * 60).unwrap()
     }
 
+    fn full_abmeldung(person_id: &str) -> Abmeldung {
+        Abmeldung {
+            person_id: Uuid::parse_str(person_id).unwrap(),
+            von: None,
+            bis: None,
+            grund: None,
+        }
+    }
+
     #[test]
     fn render_without_tops() {
         let template = ProtokollTemplate {
@@ -208,18 +412,61 @@ mod tests {
                     .unwrap(),
                 typ: SitzungTyp::Normal,
                 tops: vec![],
-                antragsfrist: NaiveDate::from_ymd_opt(2022, 5, 20)
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+            },
+            raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            events: vec![],
+            todos: vec![],
+        };
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_NO_TOPS);
+    }
+
+    #[test]
+    fn render_todos() {
+        let todo = |text: &str, day| Todo {
+            text: text.to_string(),
+            since: NaiveDate::from_ymd_opt(2022, 5, day).unwrap(),
+            done: false,
+        };
+
+        let template = ProtokollTemplate {
+            sitzung: Sitzung {
+                id: Uuid::nil(),
+                datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
                     .unwrap()
-                    .and_hms_opt(0, 0, 0)
+                    .and_hms_opt(7, 30, 15)
                     .unwrap()
                     .and_local_timezone(tz_offset())
                     .unwrap(),
+                typ: SitzungTyp::Normal,
+                tops: vec![],
+                antragsfrist: None,
             },
             raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
             events: vec![],
+            todos: vec![todo("Valentin: Blumen kaufen", 13), todo("Raum buchen", 20)],
         };
 
-        assert_eq!(template.render().unwrap(), PROTOKOLL_NO_TOPS);
+        let expected = "### ToDo's\n\n\
+            - [ ] Valentin: Blumen kaufen (seit 13.05.2022)\n\
+            - [ ] Raum buchen (seit 20.05.2022)\n\n\
+            _Top endet um T Uhr._";
+
+        assert!(template.render().unwrap().contains(expected));
     }
 
     #[test]
@@ -235,15 +482,21 @@ mod tests {
                     .unwrap(),
                 typ: SitzungTyp::VV,
                 tops: vec![],
-                antragsfrist: NaiveDate::from_ymd_opt(2022, 5, 20)
-                    .unwrap()
-                    .and_hms_opt(0, 0, 0)
-                    .unwrap()
-                    .and_local_timezone(tz_offset())
-                    .unwrap(),
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
             },
             raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
             events: vec![],
+            todos: vec![],
         };
 
         assert_eq!(template.render().unwrap(), PROTOKOLL_VV);
@@ -277,6 +530,10 @@ mod tests {
                                 .unwrap()
                                 .and_local_timezone(tz_offset())
                                 .unwrap(),
+                            antragsteller: vec![],
+                            betrag: None,
+                            haushaltstitel: None,
+                            anhaenge: vec![],
                         }],
                     },
                     Top {
@@ -295,6 +552,10 @@ mod tests {
                                     .unwrap()
                                     .and_local_timezone(tz_offset())
                                     .unwrap(),
+                                antragsteller: vec![],
+                                betrag: None,
+                                haushaltstitel: None,
+                                anhaenge: vec![],
                             },
                             Antrag {
                                 titel: "Hahn für Voltzapfanlage".to_string(),
@@ -306,22 +567,213 @@ mod tests {
                                     .unwrap()
                                     .and_local_timezone(tz_offset())
                                     .unwrap(),
+                                antragsteller: vec![],
+                                betrag: None,
+                                haushaltstitel: None,
+                                anhaenge: vec![],
                             },
                         ],
                     },
                 ],
-                antragsfrist: NaiveDate::from_ymd_opt(2022, 5, 20)
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+            },
+            events: vec![],
+            raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            todos: vec![],
+        };
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_TOPS);
+    }
+
+    #[test]
+    fn render_with_other_tops() {
+        let erstellt_am = NaiveDate::from_ymd_opt(2022, 5, 17)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(tz_offset())
+            .unwrap();
+
+        let template = ProtokollTemplate {
+            sitzung: Sitzung {
+                id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
+                datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
                     .unwrap()
-                    .and_hms_opt(0, 0, 0)
+                    .and_hms_opt(7, 30, 15)
                     .unwrap()
                     .and_local_timezone(tz_offset())
                     .unwrap(),
+                typ: SitzungTyp::Normal,
+                tops: vec![
+                    Top {
+                        name: "Blumen".to_string(),
+                        weight: 1,
+                        typ: TopTyp::Normal,
+                        inhalt: "inhalt".to_string(),
+                        antraege: vec![Antrag {
+                            titel: "A".to_string(),
+                            antragstext: "T".to_string(),
+                            begruendung: "B".to_string(),
+                            erstellt_am,
+                            antragsteller: vec![],
+                            betrag: None,
+                            haushaltstitel: None,
+                            anhaenge: vec![],
+                        }],
+                    },
+                    Top {
+                        name: "Bericht Finanzen".to_string(),
+                        weight: 2,
+                        typ: TopTyp::Bericht,
+                        inhalt: "geld".to_string(),
+                        antraege: vec![],
+                    },
+                    Top {
+                        name: "Wahl Protokoll".to_string(),
+                        weight: 0,
+                        typ: TopTyp::Regularia,
+                        inhalt: "wahl".to_string(),
+                        antraege: vec![],
+                    },
+                    Top {
+                        name: "Pizza".to_string(),
+                        weight: 5,
+                        typ: TopTyp::Verschiedenes,
+                        inhalt: "pizza".to_string(),
+                        antraege: vec![Antrag {
+                            titel: "P".to_string(),
+                            antragstext: "T".to_string(),
+                            begruendung: "B".to_string(),
+                            erstellt_am,
+                            antragsteller: vec![],
+                            betrag: None,
+                            haushaltstitel: None,
+                            anhaenge: vec![],
+                        }],
+                    },
+                    Top {
+                        name: "Bericht Ersti".to_string(),
+                        weight: 1,
+                        typ: TopTyp::Bericht,
+                        inhalt: "ersti".to_string(),
+                        antraege: vec![],
+                    },
+                ],
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
             },
             events: vec![],
             raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            todos: vec![],
         };
 
-        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_TOPS);
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_OTHER_TOPS);
+    }
+
+    #[test]
+    fn render_with_antrag_metadata() {
+        let erstellt_am = NaiveDate::from_ymd_opt(2022, 5, 17)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(tz_offset())
+            .unwrap();
+
+        let template = ProtokollTemplate {
+            sitzung: Sitzung {
+                id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
+                datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                    .unwrap()
+                    .and_hms_opt(7, 30, 15)
+                    .unwrap()
+                    .and_local_timezone(tz_offset())
+                    .unwrap(),
+                typ: SitzungTyp::Normal,
+                tops: vec![Top {
+                    name: "Geld".to_string(),
+                    weight: 1,
+                    typ: TopTyp::Normal,
+                    inhalt: "inhalt".to_string(),
+                    antraege: vec![
+                        Antrag {
+                            titel: "A".to_string(),
+                            antragstext: "T".to_string(),
+                            begruendung: "B".to_string(),
+                            erstellt_am,
+                            antragsteller: vec![
+                                Person {
+                                    id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef")
+                                        .unwrap(),
+                                    name: "Valentin".to_string(),
+                                },
+                                Person {
+                                    id: Uuid::parse_str("8296be5b-4d66-4dc7-bec9-48ea851056cb")
+                                        .unwrap(),
+                                    name: "Elif".to_string(),
+                                },
+                            ],
+                            betrag: Some(Betrag(25000)),
+                            haushaltstitel: Some("Erstiarbeit".to_string()),
+                            anhaenge: vec![
+                                Anhang {
+                                    name: Some("Angebot".to_string()),
+                                    url: "https://example.org/angebot.pdf".parse().unwrap(),
+                                },
+                                Anhang {
+                                    name: None,
+                                    url: "https://example.org/b.pdf".parse().unwrap(),
+                                },
+                            ],
+                        },
+                        Antrag {
+                            titel: "C".to_string(),
+                            antragstext: "T".to_string(),
+                            begruendung: "B".to_string(),
+                            erstellt_am,
+                            antragsteller: vec![],
+                            betrag: Some(Betrag(1299)),
+                            haushaltstitel: None,
+                            anhaenge: vec![],
+                        },
+                    ],
+                }],
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+            },
+            events: vec![],
+            raete: vec![],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            todos: vec![],
+        };
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_ANTRAG_METADATA);
     }
 
     #[test]
@@ -337,53 +789,132 @@ mod tests {
                     .unwrap(),
                 typ: SitzungTyp::Normal,
                 tops: vec![],
-                antragsfrist: NaiveDate::from_ymd_opt(2022, 5, 20)
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+            },
+            raete: vec![
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef").unwrap(),
+                    name: "Valentin".to_string(),
+                    abmeldung: None,
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("f4e53c93-fc62-4977-a559-cb4d375f0c0e").unwrap(),
+                    name: "Jonas \"Kooptimus\"".to_string(),
+                    abmeldung: None,
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("b3f8d4b2-69c0-420c-b606-187d5aac0401").unwrap(),
+                    name: "Marcel \"Markal\"".to_string(),
+                    abmeldung: None,
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("8296be5b-4d66-4dc7-bec9-48ea851056cb").unwrap(),
+                    name: "Elif".to_string(),
+                    abmeldung: Some(full_abmeldung("8296be5b-4d66-4dc7-bec9-48ea851056cb")),
+                    anwesend: false,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("c82cdc91-9152-4201-a229-b2248ab4dcd2").unwrap(),
+                    name: "Australian".to_string(),
+                    abmeldung: Some(full_abmeldung("c82cdc91-9152-4201-a229-b2248ab4dcd2")),
+                    anwesend: false,
+                },
+            ],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            events: vec![],
+            todos: vec![],
+        };
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_RÄTE);
+    }
+
+    #[test]
+    fn render_with_participants() {
+        let template = ProtokollTemplate {
+            sitzung: Sitzung {
+                id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
+                datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
                     .unwrap()
-                    .and_hms_opt(0, 0, 0)
+                    .and_hms_opt(7, 30, 15)
                     .unwrap()
                     .and_local_timezone(tz_offset())
                     .unwrap(),
+                typ: SitzungTyp::Normal,
+                tops: vec![],
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
             },
             raete: vec![
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef").unwrap(),
                     name: "Valentin".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: true,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("f4e53c93-fc62-4977-a559-cb4d375f0c0e").unwrap(),
                     name: "Jonas \"Kooptimus\"".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: true,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("b3f8d4b2-69c0-420c-b606-187d5aac0401").unwrap(),
                     name: "Marcel \"Markal\"".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: true,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("8296be5b-4d66-4dc7-bec9-48ea851056cb").unwrap(),
                     name: "Elif".to_string(),
-                    abgemeldet: true,
+                    abmeldung: Some(full_abmeldung("8296be5b-4d66-4dc7-bec9-48ea851056cb")),
                     anwesend: false,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("c82cdc91-9152-4201-a229-b2248ab4dcd2").unwrap(),
                     name: "Australian".to_string(),
-                    abgemeldet: true,
+                    abmeldung: Some(full_abmeldung("c82cdc91-9152-4201-a229-b2248ab4dcd2")),
                     anwesend: false,
                 },
             ],
+            gaeste: vec![Person {
+                id: Uuid::parse_str("0b5b5d0c-2a5f-4c4e-9f0b-7f1f4b0c9a11").unwrap(),
+                name: "Florian".to_string(),
+            }],
+            redeleitung: Some(Person {
+                id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef").unwrap(),
+                name: "Valentin".to_string(),
+            }),
+            protokoll: Some(Person {
+                id: Uuid::parse_str("0b5b5d0c-2a5f-4c4e-9f0b-7f1f4b0c9a11").unwrap(),
+                name: "Florian".to_string(),
+            }),
             events: vec![],
+            todos: vec![],
         };
 
-        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_RÄTE);
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_PARTICIPANTS);
     }
 
     #[test]
-    fn render_with_räte_no_beschluss() {
+    fn render_with_partial_abmeldungen() {
         let template = ProtokollTemplate {
             sitzung: Sitzung {
                 id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
@@ -395,51 +926,349 @@ mod tests {
                     .unwrap(),
                 typ: SitzungTyp::Normal,
                 tops: vec![],
-                antragsfrist: NaiveDate::from_ymd_opt(2022, 5, 20)
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
+            },
+            raete: vec![
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef").unwrap(),
+                    name: "Valentin".to_string(),
+                    abmeldung: None,
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("f4e53c93-fc62-4977-a559-cb4d375f0c0e").unwrap(),
+                    name: "Jonas \"Kooptimus\"".to_string(),
+                    abmeldung: Some(Abmeldung {
+                        bis: Some(
+                            NaiveDate::from_ymd_opt(2022, 5, 27)
+                                .unwrap()
+                                .and_hms_opt(20, 0, 0)
+                                .unwrap()
+                                .and_local_timezone(tz_offset())
+                                .unwrap(),
+                        ),
+                        grund: Some(AbmeldungGrund::Arbeit),
+                        ..full_abmeldung("f4e53c93-fc62-4977-a559-cb4d375f0c0e")
+                    }),
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("b3f8d4b2-69c0-420c-b606-187d5aac0401").unwrap(),
+                    name: "Marcel \"Markal\"".to_string(),
+                    abmeldung: Some(Abmeldung {
+                        von: Some(
+                            NaiveDate::from_ymd_opt(2022, 5, 27)
+                                .unwrap()
+                                .and_hms_opt(19, 0, 0)
+                                .unwrap()
+                                .and_local_timezone(tz_offset())
+                                .unwrap(),
+                        ),
+                        ..full_abmeldung("b3f8d4b2-69c0-420c-b606-187d5aac0401")
+                    }),
+                    anwesend: true,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("8296be5b-4d66-4dc7-bec9-48ea851056cb").unwrap(),
+                    name: "Elif".to_string(),
+                    abmeldung: Some(full_abmeldung("8296be5b-4d66-4dc7-bec9-48ea851056cb")),
+                    anwesend: false,
+                },
+                PersonWithAbmeldung {
+                    id: Uuid::parse_str("c82cdc91-9152-4201-a229-b2248ab4dcd2").unwrap(),
+                    name: "Australian".to_string(),
+                    abmeldung: Some(Abmeldung {
+                        grund: Some(AbmeldungGrund::Lehrveranstaltung),
+                        ..full_abmeldung("c82cdc91-9152-4201-a229-b2248ab4dcd2")
+                    }),
+                    anwesend: false,
+                },
+            ],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
+            events: vec![],
+            todos: vec![],
+        };
+
+        assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_ABMELDUNGEN);
+    }
+
+    #[test]
+    fn render_with_räte_no_beschluss() {
+        let template = ProtokollTemplate {
+            sitzung: Sitzung {
+                id: Uuid::parse_str("efc794db-5d32-4186-a7d6-5fe6eee70452").unwrap(),
+                datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
                     .unwrap()
-                    .and_hms_opt(0, 0, 0)
+                    .and_hms_opt(7, 30, 15)
                     .unwrap()
                     .and_local_timezone(tz_offset())
                     .unwrap(),
+                typ: SitzungTyp::Normal,
+                tops: vec![],
+                antragsfrist: Some(
+                    NaiveDate::from_ymd_opt(2022, 5, 20)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap()
+                        .and_local_timezone(tz_offset())
+                        .unwrap(),
+                ),
             },
             raete: vec![
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("22b6b758-741c-429f-9e96-65fc289fcfef").unwrap(),
                     name: "Valentin".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: true,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("f4e53c93-fc62-4977-a559-cb4d375f0c0e").unwrap(),
                     name: "Jonas \"Kooptimus\"".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: false,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("b3f8d4b2-69c0-420c-b606-187d5aac0401").unwrap(),
                     name: "Marcel \"Markal\"".to_string(),
-                    abgemeldet: false,
+                    abmeldung: None,
                     anwesend: false,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("8296be5b-4d66-4dc7-bec9-48ea851056cb").unwrap(),
                     name: "Elif".to_string(),
-                    abgemeldet: true,
+                    abmeldung: Some(full_abmeldung("8296be5b-4d66-4dc7-bec9-48ea851056cb")),
                     anwesend: false,
                 },
                 PersonWithAbmeldung {
                     id: Uuid::parse_str("c82cdc91-9152-4201-a229-b2248ab4dcd2").unwrap(),
                     name: "Australian".to_string(),
-                    abgemeldet: true,
+                    abmeldung: Some(full_abmeldung("c82cdc91-9152-4201-a229-b2248ab4dcd2")),
                     anwesend: false,
                 },
             ],
+            gaeste: vec![],
+            redeleitung: None,
+            protokoll: None,
             events: vec![],
+            todos: vec![],
         };
 
         assert_eq!(template.render().unwrap(), PROTOKOLL_WITH_RÄTE_NO_BESCHLUSS);
     }
 
+    #[test]
+    fn normal_tops_by_weight() {
+        let top = |name: &str, weight: i64, typ: TopTyp| Top {
+            name: name.to_string(),
+            weight,
+            typ,
+            inhalt: String::new(),
+            antraege: vec![],
+        };
+
+        let tops = vec![
+            top("Volt Zapfanlage", 3, TopTyp::Normal),
+            top("Bericht", 0, TopTyp::Bericht),
+            top("Blumen für Valentin", 1, TopTyp::Normal),
+            top("Erstiwoche", 2, TopTyp::Normal),
+        ];
+
+        let result: Vec<&str> = super::filters::normal_tops(&tops, &())
+            .unwrap()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+
+        assert_eq!(
+            result,
+            vec!["Blumen für Valentin", "Erstiwoche", "Volt Zapfanlage"]
+        );
+    }
+
+    #[test]
+    fn late_antraege() {
+        let datetime = NaiveDate::from_ymd_opt(2022, 5, 27)
+            .unwrap()
+            .and_hms_opt(18, 30, 0)
+            .unwrap()
+            .and_local_timezone(tz_offset())
+            .unwrap();
+
+        let antrag = |titel: &str, day: u32| Antrag {
+            titel: titel.to_string(),
+            antragstext: String::new(),
+            begruendung: String::new(),
+            erstellt_am: NaiveDate::from_ymd_opt(2022, 5, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(tz_offset())
+                .unwrap(),
+            antragsteller: vec![],
+            betrag: None,
+            haushaltstitel: None,
+            anhaenge: vec![],
+        };
+
+        let mut sitzung = Sitzung {
+            id: Uuid::nil(),
+            datetime,
+            typ: SitzungTyp::Normal,
+            tops: vec![Top {
+                name: "Volt".to_string(),
+                weight: 1,
+                typ: TopTyp::Normal,
+                inhalt: String::new(),
+                antraege: vec![antrag("Tank", 23), antrag("Hahn (neu), 2.0", 25)],
+            }],
+            antragsfrist: None,
+        };
+
+        assert!(super::filters::nicht_fristgerechte_antraege(&sitzung, &())
+            .unwrap()
+            .is_empty());
+
+        sitzung.fill_antragsfrist(3, &crate::timezone::DEFAULT_TIMEZONE);
+
+        let late = super::filters::nicht_fristgerechte_antraege(&sitzung, &()).unwrap();
+
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].titel, "Hahn (neu), 2.0");
+        assert_eq!(
+            super::filters::antrag_anchor(late[0], &()).unwrap(),
+            "antrag-hahn-neu-20"
+        );
+    }
+
+    #[test]
+    fn event_format() {
+        let start = NaiveDate::from_ymd_opt(2022, 5, 30)
+            .unwrap()
+            .and_hms_opt(19, 0, 0)
+            .unwrap()
+            .and_local_timezone(tz_offset())
+            .unwrap();
+
+        let event = Event {
+            title: Some("Spieleabend".to_string()),
+            location: Some("25.12.O2.35".to_string()),
+            start: EventTime::DateTime(start),
+            end: None,
+        };
+
+        let without_location = Event {
+            title: Some("Spieleabend".to_string()),
+            location: None,
+            start: EventTime::DateTime(start),
+            end: None,
+        };
+
+        let without_title = Event {
+            title: None,
+            location: Some("25.12.O2.35".to_string()),
+            start: EventTime::DateTime(start),
+            end: None,
+        };
+
+        let all_day = Event {
+            title: Some("Tag der offenen Tür".to_string()),
+            location: None,
+            start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 30).unwrap()),
+            end: Some(EventTime::Date(
+                NaiveDate::from_ymd_opt(2022, 5, 31).unwrap(),
+            )),
+        };
+
+        let multi_day = Event {
+            title: Some("Ersti-Fahrt".to_string()),
+            location: None,
+            start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 12).unwrap()),
+            end: Some(EventTime::Date(
+                NaiveDate::from_ymd_opt(2022, 5, 15).unwrap(),
+            )),
+        };
+
+        let multi_month = Event {
+            title: Some("Programmierkurs".to_string()),
+            location: Some("25.12.O2.35".to_string()),
+            start: EventTime::DateTime(start),
+            end: Some(EventTime::DateTime(
+                NaiveDate::from_ymd_opt(2022, 6, 2)
+                    .unwrap()
+                    .and_hms_opt(16, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(tz_offset())
+                    .unwrap(),
+            )),
+        };
+
+        assert_eq!(
+            super::filters::event_format(&event, &()).unwrap(),
+            "30.05. Spieleabend 19:00 Uhr 25.12.O2.35"
+        );
+        assert_eq!(
+            super::filters::event_format(&without_location, &()).unwrap(),
+            "30.05. Spieleabend 19:00 Uhr"
+        );
+        assert_eq!(
+            super::filters::event_format(&without_title, &()).unwrap(),
+            "30.05. 19:00 Uhr 25.12.O2.35"
+        );
+        assert_eq!(
+            super::filters::event_format(&all_day, &()).unwrap(),
+            "30.05. Tag der offenen Tür"
+        );
+        assert_eq!(
+            super::filters::event_format(&multi_day, &()).unwrap(),
+            "12.–14.05. Ersti-Fahrt"
+        );
+        assert_eq!(
+            super::filters::event_format(&multi_month, &()).unwrap(),
+            "30.05.–02.06. Programmierkurs 25.12.O2.35"
+        );
+
+        let until_midnight = Event {
+            title: Some("Spieleabend".to_string()),
+            location: None,
+            start: EventTime::DateTime(start),
+            end: Some(EventTime::DateTime(
+                NaiveDate::from_ymd_opt(2022, 5, 31)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(tz_offset())
+                    .unwrap(),
+            )),
+        };
+
+        let multi_year = Event {
+            title: Some("Auslandssemester".to_string()),
+            location: None,
+            start: EventTime::Date(NaiveDate::from_ymd_opt(2022, 5, 30).unwrap()),
+            end: Some(EventTime::Date(
+                NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(),
+            )),
+        };
+
+        assert_eq!(
+            super::filters::event_format(&until_midnight, &()).unwrap(),
+            "30.05. Spieleabend 19:00 Uhr"
+        );
+        assert_eq!(
+            super::filters::event_format(&multi_year, &()).unwrap(),
+            "30.05.–31.05. Auslandssemester"
+        );
+    }
+
     #[test]
     fn find_protokoll_date() {
         let protokoll = r#"---
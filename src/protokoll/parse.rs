@@ -0,0 +1,487 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use markdown::mdast;
+
+use super::{betrag::Betrag, find_frontmatter, find_protokoll_date, parse_protokoll};
+use crate::SitzungTyp;
+
+const ANWESENDE_HEADING: &str = "Anwesende Rätys";
+
+/// the parts of a written protokoll, which are of interest after the sitzung
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedProtokoll {
+    pub date: NaiveDate,
+    pub sitzung_kind: SitzungTyp,
+    /// whether the protokoll isnt published yet
+    pub draft: bool,
+    /// the names listed under 'Anwesende Rätys'
+    pub anwesende: Vec<String>,
+    pub tops: Vec<ParsedTop>,
+    /// the task list items of all tops
+    pub todos: Vec<Todo>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedTop {
+    pub title: String,
+    pub antraege: Vec<ParsedAntrag>,
+    /// all vote blocks of this top, including those belonging to an antrag
+    pub abstimmungen: Vec<Abstimmung>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedAntrag {
+    pub titel: String,
+    pub betrag: Option<Betrag>,
+    pub haushaltstitel: Option<String>,
+    pub abstimmung: Option<Abstimmung>,
+}
+
+/// a task list item. open ones are carried over into the next protokoll.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Todo {
+    /// the markdown of the item, without its checkbox and date
+    pub text: String,
+    /// the date of the sitzung in which the todo came up first
+    pub since: NaiveDate,
+    /// whether the item is checked
+    pub done: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Abstimmung {
+    pub angenommen: bool,
+    pub text: String,
+    pub zustimmungen: Option<u32>,
+    pub gegenstimmen: Option<u32>,
+    pub enthaltungen: Option<u32>,
+}
+
+impl Abstimmung {
+    /// whether this vote only establishes the dringlichkeit of a late antrag
+    pub fn is_dringlichkeit(&self) -> bool {
+        self.text.starts_with("Die Dringlichkeit")
+    }
+
+    /// whether any votes were counted, which isnt the case for the placeholder of the
+    /// template, e.g. 'n Zustimmen'
+    pub const fn has_counts(&self) -> bool {
+        self.zustimmungen.is_some() || self.gegenstimmen.is_some() || self.enthaltungen.is_some()
+    }
+}
+
+impl ParsedProtokoll {
+    pub fn antraege(&self) -> impl Iterator<Item = &ParsedAntrag> {
+        self.tops.iter().flat_map(|t| &t.antraege)
+    }
+
+    /// the accepted votes. the placeholders of the template arent counted, as nobody
+    /// voted on them yet.
+    pub fn beschluesse(&self) -> impl Iterator<Item = &Abstimmung> {
+        self.tops
+            .iter()
+            .flat_map(|t| &t.abstimmungen)
+            .filter(|a| a.angenommen && a.has_counts())
+    }
+
+    pub fn open_todos(&self) -> impl Iterator<Item = &Todo> {
+        self.todos.iter().filter(|t| !t.done)
+    }
+}
+
+/// parses a written protokoll. tops are recognized by their 'Top N:' headings, anträge
+/// by their 'Antrag:' headings and votes by 'vote-success' or 'vote-failure' code blocks.
+pub fn parse(content: &str) -> Result<ParsedProtokoll> {
+    let mdast = parse_protokoll(content)?;
+    let frontmatter = find_frontmatter(&mdast)?;
+    let date = find_protokoll_date(&frontmatter)?;
+
+    let mut anwesende: Vec<String> = vec![];
+    let mut tops: Vec<ParsedTop> = vec![];
+    let mut todos: Vec<Todo> = vec![];
+    let mut in_anwesende = false;
+
+    for node in mdast.children().map_or(&[][..], |c| c.as_slice()) {
+        if let mdast::Node::Heading(_) = node {
+            in_anwesende = node.to_string().trim() == ANWESENDE_HEADING;
+        }
+
+        match node {
+            mdast::Node::List(list) if in_anwesende => {
+                for item in &list.children {
+                    let text = item.to_string();
+                    // partially abgemeldete räte are listed as 'name, entschuldigt (...)'
+                    let name = text
+                        .split_once(", entschuldigt")
+                        .map_or(text.as_str(), |(n, _)| n);
+
+                    anwesende.push(name.trim().to_string());
+                }
+            }
+            mdast::Node::List(_) if !tops.is_empty() => {
+                collect_todos(node, content, date, &mut todos);
+            }
+            mdast::Node::Heading(heading) if heading.depth == 2 => {
+                tops.push(ParsedTop {
+                    title: top_title(&node.to_string()).to_string(),
+                    antraege: vec![],
+                    abstimmungen: vec![],
+                });
+            }
+            mdast::Node::Heading(heading) if heading.depth > 2 => {
+                let text = node.to_string();
+
+                if let (Some(top), Some(titel)) = (tops.last_mut(), text.strip_prefix("Antrag:")) {
+                    top.antraege.push(ParsedAntrag {
+                        titel: titel.trim().to_string(),
+                        betrag: None,
+                        haushaltstitel: None,
+                        abstimmung: None,
+                    });
+                }
+            }
+            mdast::Node::Paragraph(_) => {
+                let Some(antrag) = tops.last_mut().and_then(|t| t.antraege.last_mut()) else {
+                    continue;
+                };
+
+                for line in node_lines(node) {
+                    if let Some(betrag) = line.strip_prefix("Betrag:") {
+                        antrag.betrag = betrag.parse().ok();
+                    } else if let Some(titel) = line.strip_prefix("Haushaltstitel:") {
+                        antrag.haushaltstitel = Some(titel.trim().to_string());
+                    }
+                }
+            }
+            mdast::Node::Code(code) => {
+                let Some(top) = tops.last_mut() else {
+                    continue;
+                };
+
+                let Some(abstimmung) = parse_abstimmung(code) else {
+                    continue;
+                };
+
+                if let Some(antrag) = top.antraege.last_mut() {
+                    if antrag.abstimmung.is_none() && !abstimmung.is_dringlichkeit() {
+                        antrag.abstimmung = Some(abstimmung.clone());
+                    }
+                }
+
+                top.abstimmungen.push(abstimmung);
+            }
+            _ => (),
+        }
+    }
+
+    Ok(ParsedProtokoll {
+        date,
+        sitzung_kind: frontmatter.sitzung_kind.unwrap_or(SitzungTyp::Normal),
+        draft: frontmatter.draft == Some(true),
+        anwesende,
+        tops,
+        todos,
+    })
+}
+
+/// collects the items of the task list `node` and the lists nested within
+fn collect_todos(node: &mdast::Node, content: &str, date: NaiveDate, result: &mut Vec<Todo>) {
+    for child in node.children().map_or(&[][..], |c| c.as_slice()) {
+        if let mdast::Node::ListItem(item) = child {
+            let paragraph = item
+                .children
+                .iter()
+                .find(|c| matches!(c, mdast::Node::Paragraph(_)))
+                .and_then(mdast::Node::position)
+                .and_then(|p| content.get(p.start.offset..p.end.offset));
+
+            if let (Some(checked), Some(text)) = (item.checked, paragraph) {
+                result.push(parse_todo(text, date, checked));
+            }
+        }
+
+        collect_todos(child, content, date, result);
+    }
+}
+
+/// splits off the date a carried over todo came up first, e.g. '(seit 27.05.2022)'
+fn parse_todo(text: &str, date: NaiveDate, done: bool) -> Todo {
+    let since = text
+        .trim()
+        .strip_suffix(')')
+        .and_then(|t| t.rsplit_once("(seit "))
+        .and_then(|(rest, suffix)| {
+            NaiveDate::parse_from_str(suffix, "%d.%m.%Y")
+                .ok()
+                .map(|d| (rest, d))
+        });
+
+    let (todo_text, todo_since) = since.unwrap_or((text, date));
+
+    Todo {
+        text: todo_text.trim().to_string(),
+        since: todo_since,
+        done,
+    }
+}
+
+/// the title of a top heading, without its 'Top N:' prefix
+pub(super) fn top_title(heading: &str) -> &str {
+    heading
+        .split_once(':')
+        .filter(|(prefix, _)| prefix.trim_start().starts_with("Top"))
+        .map_or(heading, |(_, title)| title)
+        .trim()
+}
+
+/// the text lines of a paragraph, split at soft and hard line breaks
+fn node_lines(node: &mdast::Node) -> Vec<String> {
+    let mut text = String::new();
+
+    for child in node.children().map_or(&[][..], |c| c.as_slice()) {
+        match child {
+            mdast::Node::Break(_) => text.push('\n'),
+            _ => text.push_str(&child.to_string()),
+        }
+    }
+
+    text.lines().map(|l| l.trim().to_string()).collect()
+}
+
+pub(super) fn parse_abstimmung(code: &mdast::Code) -> Option<Abstimmung> {
+    let angenommen = match code.lang.as_deref() {
+        Some("vote-success") => true,
+        Some("vote-failure") => false,
+        _ => return None,
+    };
+
+    let mut abstimmung = Abstimmung {
+        angenommen,
+        text: String::new(),
+        zustimmungen: None,
+        gegenstimmen: None,
+        enthaltungen: None,
+    };
+
+    let mut text_lines = vec![];
+
+    for line in code.value.lines() {
+        let Some(result) = line.trim().strip_prefix("Abstimmung:") else {
+            text_lines.push(line);
+            continue;
+        };
+
+        // e.g. "5 Zustimmen, 1 Gegenstimmen, 0 Enthaltungen"
+        for part in result.split(',') {
+            let mut words = part.split_whitespace();
+            let count = words.next().and_then(|n| n.parse().ok());
+            let kind = words.next().unwrap_or_default().to_lowercase();
+
+            if kind.starts_with("zustimm") || kind.starts_with("dafür") {
+                abstimmung.zustimmungen = count;
+            } else if kind.starts_with("gegenstimm") || kind.starts_with("dagegen") {
+                abstimmung.gegenstimmen = count;
+            } else if kind.starts_with("enthaltung") {
+                abstimmung.enthaltungen = count;
+            }
+        }
+    }
+
+    abstimmung.text = text_lines.join("\n").trim().to_string();
+
+    Some(abstimmung)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::{Abstimmung, ParsedAntrag, ParsedProtokoll, ParsedTop, Todo};
+    use crate::{Betrag, SitzungTyp};
+
+    static PROTOKOLL_WITH_ANTRAG_METADATA: &str =
+        include_str!("../../tests/protokoll-with-antrag-metadata.md");
+    static PROTOKOLL_WITH_RÄTE: &str = include_str!("../../tests/protokoll-with-rate.md");
+
+    #[test]
+    fn parse_rendered_protokoll() {
+        let result = super::parse(PROTOKOLL_WITH_ANTRAG_METADATA).unwrap();
+
+        let placeholder = |angenommen| Abstimmung {
+            angenommen,
+            text: "T".to_string(),
+            zustimmungen: None,
+            gegenstimmen: None,
+            enthaltungen: None,
+        };
+
+        let expected = ParsedProtokoll {
+            date: NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            sitzung_kind: SitzungTyp::Normal,
+            draft: true,
+            anwesende: vec![],
+            tops: vec![
+                ParsedTop {
+                    title: "Regularia".to_string(),
+                    antraege: vec![],
+                    abstimmungen: vec![],
+                },
+                ParsedTop {
+                    title: "Berichte, Mail und Post".to_string(),
+                    antraege: vec![],
+                    abstimmungen: vec![],
+                },
+                ParsedTop {
+                    title: "Geld".to_string(),
+                    antraege: vec![
+                        ParsedAntrag {
+                            titel: "A".to_string(),
+                            betrag: Some(Betrag(25000)),
+                            haushaltstitel: Some("Erstiarbeit".to_string()),
+                            abstimmung: Some(placeholder(true)),
+                        },
+                        ParsedAntrag {
+                            titel: "C".to_string(),
+                            betrag: Some(Betrag(1299)),
+                            haushaltstitel: None,
+                            abstimmung: Some(placeholder(true)),
+                        },
+                    ],
+                    abstimmungen: vec![placeholder(true), placeholder(true)],
+                },
+                ParsedTop {
+                    title: "Verschiedenes".to_string(),
+                    antraege: vec![],
+                    abstimmungen: vec![],
+                },
+            ],
+            todos: vec![],
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_abstimmungen() {
+        let protokoll = r#"---
+date: "2022-05-27"
+sitzung-kind: "vv"
+---
+
+## Top 2: Erstiwoche
+
+```vote-success
+Die Fachschaft beschließt die Erstiwoche.
+
+Abstimmung: 7 Zustimmen, 1 Gegenstimmen, 2 Enthaltungen
+```
+
+### Antrag: Grillen
+
+_Nicht fristgerecht eingereicht – die Dringlichkeit muss festgestellt werden._
+
+```vote-success
+Die Dringlichkeit des Antrags wird festgestellt.
+```
+
+Betrag: 1.250,00 €
+
+```vote-failure
+Die Fachschaft beschließt zu grillen.
+
+Abstimmung: 3 Zustimmen, 5 Gegenstimmen, 2 Enthaltungen
+```
+"#;
+
+        let result = super::parse(protokoll).unwrap();
+
+        let erstiwoche = Abstimmung {
+            angenommen: true,
+            text: "Die Fachschaft beschließt die Erstiwoche.".to_string(),
+            zustimmungen: Some(7),
+            gegenstimmen: Some(1),
+            enthaltungen: Some(2),
+        };
+
+        let grillen = Abstimmung {
+            angenommen: false,
+            text: "Die Fachschaft beschließt zu grillen.".to_string(),
+            zustimmungen: Some(3),
+            gegenstimmen: Some(5),
+            enthaltungen: Some(2),
+        };
+
+        let dringlichkeit = Abstimmung {
+            angenommen: true,
+            text: "Die Dringlichkeit des Antrags wird festgestellt.".to_string(),
+            zustimmungen: None,
+            gegenstimmen: None,
+            enthaltungen: None,
+        };
+
+        let expected = ParsedProtokoll {
+            date: NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            sitzung_kind: SitzungTyp::VV,
+            draft: false,
+            anwesende: vec![],
+            tops: vec![ParsedTop {
+                title: "Erstiwoche".to_string(),
+                antraege: vec![ParsedAntrag {
+                    titel: "Grillen".to_string(),
+                    betrag: Some(Betrag(125_000)),
+                    haushaltstitel: None,
+                    abstimmung: Some(grillen.clone()),
+                }],
+                abstimmungen: vec![erstiwoche.clone(), dringlichkeit, grillen],
+            }],
+            todos: vec![],
+        };
+
+        assert_eq!(result, expected);
+        assert_eq!(result.beschluesse().collect::<Vec<_>>(), vec![&erstiwoche]);
+    }
+
+    #[test]
+    fn parse_anwesende() {
+        let result = super::parse(PROTOKOLL_WITH_RÄTE).unwrap();
+
+        assert_eq!(
+            result.anwesende,
+            vec!["Valentin", "Jonas \"Kooptimus\"", "Marcel \"Markal\""]
+        );
+    }
+
+    #[test]
+    fn parse_todos() {
+        let content = "---\ndate: \"2022-05-27\"\n---\n\n\
+            - [ ] before the first top\n\n\
+            ## Top 1: Berichte, Mail und Post\n\n\
+            ### ToDo's\n\n\
+            - [ ] Valentin: Blumen kaufen (seit 13.05.2022)\n\
+            - [x] Zapfanlage bestellen\n\
+            - Mail beantworten\n\n\
+            ## Top 2: Erstiwoche\n\n\
+            - [ ] [Raum](https://fscs.hhu.de/raum) buchen\n    \
+            - [ ] Schlüssel abholen\n";
+
+        let result = super::parse(content).unwrap();
+
+        let todo = |text: &str, day, done| Todo {
+            text: text.to_string(),
+            since: NaiveDate::from_ymd_opt(2022, 5, day).unwrap(),
+            done,
+        };
+
+        assert_eq!(
+            result.todos,
+            vec![
+                todo("Valentin: Blumen kaufen", 13, false),
+                todo("Zapfanlage bestellen", 27, true),
+                todo("[Raum](https://fscs.hhu.de/raum) buchen", 27, false),
+                todo("Schlüssel abholen", 27, false),
+            ]
+        );
+    }
+}
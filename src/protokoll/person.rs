@@ -1,73 +1,173 @@
 use std::fmt::{Display, Formatter};
 
-use anyhow::{Context, Result};
-use reqwest::blocking::Client;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset, Timelike};
+use chrono_tz::Tz;
 use serde::Deserialize;
-use url::Url;
 use uuid::Uuid;
 
 use super::Sitzung;
+use crate::{api::ApiClient, timezone};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct PersonWithAbmeldung {
     pub id: Uuid,
     pub name: String,
-    pub abgemeldet: bool,
+    pub abmeldung: Option<Abmeldung>,
     pub anwesend: bool,
 }
 
+impl PersonWithAbmeldung {
+    pub const fn is_abgemeldet(&self) -> bool {
+        self.abmeldung.is_some()
+    }
+
+    /// whether this rat is only abgemeldet for a part of the sitzung
+    pub fn is_partially_abgemeldet(&self) -> bool {
+        self.abmeldung.as_ref().is_some_and(Abmeldung::is_partial)
+    }
+}
+
 impl Display for PersonWithAbmeldung {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}", self.name.as_str(),))
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
 pub struct Person {
     pub id: Uuid,
     pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+impl Person {
+    /// whether `entry` is the id or the name of this person
+    pub fn matches(&self, entry: &str) -> bool {
+        matches_entry(self.id, &self.name, entry)
+    }
+}
+
+fn matches_entry(id: Uuid, name: &str, entry: &str) -> bool {
+    Uuid::parse_str(entry).is_ok_and(|e| e == id) || name.eq_ignore_ascii_case(entry.trim())
+}
+
+impl Display for Person {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}", self.name.as_str()))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
 pub struct Abmeldung {
     pub person_id: Uuid,
+    /// set, if the rat is only abgemeldet from this time on
+    #[serde(default)]
+    pub von: Option<DateTime<FixedOffset>>,
+    /// set, if the rat is only abgemeldet until this time
+    #[serde(default)]
+    pub bis: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub grund: Option<AbmeldungGrund>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, strum::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum AbmeldungGrund {
+    Krankheit,
+    Lehrveranstaltung,
+    Arbeit,
+    Urlaub,
+    #[serde(other)]
+    Sonstiges,
 }
 
-pub fn fetch_raete(api_url: &Url, client: &Client) -> Result<Vec<Person>> {
-    let mut endpoint = api_url.join("api/persons/by-role/")?;
-    endpoint.set_query(Some("role=Rat"));
+impl Abmeldung {
+    /// converts the time range to `tz`
+    pub fn to_timezone(&mut self, tz: &Tz) {
+        self.von = self.von.map(|von| timezone::convert(von, tz));
+        self.bis = self.bis.map(|bis| timezone::convert(bis, tz));
+    }
+
+    pub const fn is_partial(&self) -> bool {
+        self.von.is_some() || self.bis.is_some()
+    }
+
+    /// the reason and time range of this abmeldung, e.g. "Lehrveranstaltung, ab 19 Uhr".
+    /// 'Sonstiges' isnt worth mentioning and is left out.
+    pub fn details(&self) -> Option<String> {
+        let mut parts = vec![];
 
-    let response = client
-        .get(endpoint)
-        .send()
-        .context("unable to fetch räte")?
-        .error_for_status()
-        .context("unable to fetch räte")?;
+        if let Some(grund) = self
+            .grund
+            .as_ref()
+            .filter(|g| **g != AbmeldungGrund::Sonstiges)
+        {
+            parts.push(grund.to_string());
+        }
 
-    let persons = response.json().context("unable to deserialize räte")?;
+        match (self.von, self.bis) {
+            (Some(von), Some(bis)) => parts.push(format!(
+                "von {} bis {}",
+                format_uhrzeit(&von),
+                format_uhrzeit(&bis)
+            )),
+            (Some(von), None) => parts.push(format!("ab {}", format_uhrzeit(&von))),
+            (None, Some(bis)) => parts.push(format!("bis {}", format_uhrzeit(&bis))),
+            (None, None) => (),
+        }
 
-    Ok(persons)
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(", "))
+        }
+    }
 }
 
-pub fn fetch_abmeldungen(
-    api_url: &Url,
-    client: &Client,
-    sitzung: &Sitzung,
-) -> Result<Vec<Abmeldung>> {
-    let endpoint = api_url.join(format!("api/sitzungen/{}/abmeldungen/", sitzung.id).as_str())?;
+/// e.g. "19 Uhr" or "19:30 Uhr"
+fn format_uhrzeit(datetime: &DateTime<FixedOffset>) -> String {
+    if datetime.minute() == 0 {
+        format!("{} Uhr", datetime.hour())
+    } else {
+        format!("{} Uhr", datetime.format("%H:%M"))
+    }
+}
 
-    let response = client
-        .get(endpoint)
-        .send()
-        .context("unable to fetch abmeldungen")?
-        .error_for_status()
-        .context("unable to fetch abmeldungen")?;
+pub fn fetch_raete(api: &ApiClient) -> Result<Vec<Person>> {
+    fetch_persons_by_role(api, "Rat")
+}
 
-    let abmeldungen = response
-        .json()
-        .context("unable to deserialize abmeldungen")?;
+pub fn fetch_persons_by_role(api: &ApiClient, role: &str) -> Result<Vec<Person>> {
+    api.get_json("api/persons/by-role/", &[("role", role)])
+        .with_context(|| format!("unable to fetch persons with role '{}'", role))
+}
+
+/// fetches the persons with any of `roles`, who arent already in `exclude`. persons
+/// with more than one of the roles are only included once.
+pub fn fetch_guests(
+    api: &ApiClient,
+    roles: &[String],
+    exclude: &[PersonWithAbmeldung],
+) -> Result<Vec<Person>> {
+    let mut result: Vec<Person> = vec![];
+
+    for role in roles {
+        for person in fetch_persons_by_role(api, role)? {
+            let known = exclude.iter().any(|p| p.id == person.id)
+                || result.iter().any(|p| p.id == person.id);
+
+            if !known {
+                result.push(person);
+            }
+        }
+    }
 
-    Ok(abmeldungen)
+    Ok(result)
+}
+
+pub fn fetch_abmeldungen(api: &ApiClient, sitzung: &Sitzung) -> Result<Vec<Abmeldung>> {
+    api.get_json(&format!("api/sitzungen/{}/abmeldungen/", sitzung.id), &[])
+        .context("unable to fetch abmeldungen")
 }
 
 pub fn determine_abgemeldet_räte(
@@ -77,22 +177,110 @@ pub fn determine_abgemeldet_räte(
     personen
         .iter()
         .map(|p| {
-            let abgemeldet = abmeldungen.iter().any(|a| a.person_id == p.id);
+            let abmeldung = abmeldungen.iter().find(|a| a.person_id == p.id).cloned();
 
             PersonWithAbmeldung {
                 id: p.id,
                 name: p.name.to_owned(),
                 anwesend: false,
-                abgemeldet,
+                abmeldung,
             }
         })
         .collect()
 }
 
+/// marks the räte listed in `entries` as present. entries can either be names or ids.
+/// returns the entries, which dont match any rat.
+pub fn mark_present(räte: &mut [PersonWithAbmeldung], entries: &[String]) -> Vec<String> {
+    let mut unknown = vec![];
+
+    for entry in entries {
+        let matching = räte
+            .iter_mut()
+            .find(|r| matches_entry(r.id, &r.name, entry));
+
+        match matching {
+            Some(rat) => rat.anwesend = true,
+            None => unknown.push(entry.to_owned()),
+        }
+    }
+
+    unknown
+}
+
+/// marks the räte, who were present at the previous sitzung, as present again,
+/// unless they are abgemeldet for the whole sitzung
+pub fn mark_present_again(räte: &mut [PersonWithAbmeldung], previous_anwesende: &[String]) {
+    // names of former räte are of no interest here
+    let _ = mark_present(räte, previous_anwesende);
+
+    for rat in räte.iter_mut() {
+        if rat.is_abgemeldet() && !rat.is_partially_abgemeldet() {
+            rat.anwesend = false;
+        }
+    }
+}
+
+/// the persons who can lead the sitzung or write the protokoll: the present räte and
+/// `gaeste`. if the presence isnt known, the räte who arent abgemeldet for the whole
+/// sitzung are assumed to be present.
+pub fn possible_participants(
+    räte: &[PersonWithAbmeldung],
+    gaeste: &[Person],
+    presence_known: bool,
+) -> Vec<Person> {
+    räte
+        .iter()
+        .filter(|r| {
+            if presence_known {
+                r.anwesend
+            } else {
+                !r.is_abgemeldet() || r.is_partially_abgemeldet()
+            }
+        })
+        .map(|r| Person {
+            id: r.id,
+            name: r.name.clone(),
+        })
+        .chain(gaeste.iter().cloned())
+        .collect()
+}
+
+/// finds the person named by `entry` among `participants`, who takes `role`, e.g.
+/// 'redeleitung'. without an entry the role is left empty, to be filled in later.
+pub fn find_participant(
+    participants: &[Person],
+    entry: Option<&str>,
+    role: &str,
+) -> Result<Option<Person>> {
+    let Some(name) = entry else {
+        return Ok(None);
+    };
+
+    participants
+        .iter()
+        .find(|p| p.matches(name))
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| anyhow!("'{}' isnt present and cant be {}", name, role))
+}
+
+/// parses a list of present persons with one name or id per line. empty lines and
+/// lines starting with '#' are ignored.
+pub fn parse_present_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use super::{Abmeldung, Person, PersonWithAbmeldung};
+    use super::{Abmeldung, AbmeldungGrund, Person, PersonWithAbmeldung};
+    use chrono::{FixedOffset, NaiveDate};
     use pretty_assertions::assert_eq;
     use uuid::Uuid;
 
@@ -109,21 +297,26 @@ mod tests {
             },
         ];
 
-        let abmeldungen = vec![Abmeldung {
+        let abmeldung = Abmeldung {
             person_id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
-        }];
+            von: None,
+            bis: None,
+            grund: None,
+        };
+
+        let abmeldungen = vec![abmeldung.clone()];
 
         let expected = vec![
             PersonWithAbmeldung {
                 id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
                 name: "Valentin Pukhov".to_string(),
-                abgemeldet: true,
+                abmeldung: Some(abmeldung),
                 anwesend: false,
             },
             PersonWithAbmeldung {
                 id: Uuid::parse_str("444e8400-e29b-41d4-a716-446655440000").unwrap(),
                 name: "Florian Schubert".to_string(),
-                abgemeldet: false,
+                abmeldung: None,
                 anwesend: false,
             },
         ];
@@ -132,4 +325,150 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn abmeldung_details() {
+        let time = |h, m| {
+            NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+                .and_local_timezone(FixedOffset::east_opt(2 * 60 * 60).unwrap())
+                .unwrap()
+        };
+
+        let abmeldung = |von, bis, grund| Abmeldung {
+            person_id: Uuid::nil(),
+            von,
+            bis,
+            grund,
+        };
+
+        assert_eq!(abmeldung(None, None, None).details(), None);
+        assert_eq!(
+            abmeldung(None, None, Some(AbmeldungGrund::Sonstiges)).details(),
+            None
+        );
+        assert_eq!(
+            abmeldung(Some(time(19, 0)), None, None).details(),
+            Some("ab 19 Uhr".to_string())
+        );
+        assert_eq!(
+            abmeldung(None, Some(time(18, 45)), Some(AbmeldungGrund::Arbeit)).details(),
+            Some("Arbeit, bis 18:45 Uhr".to_string())
+        );
+        assert_eq!(
+            abmeldung(Some(time(18, 30)), Some(time(20, 0)), None).details(),
+            Some("von 18:30 Uhr bis 20 Uhr".to_string())
+        );
+        assert!(!abmeldung(None, None, None).is_partial());
+        assert!(abmeldung(None, Some(time(20, 0)), None).is_partial());
+    }
+
+    #[test]
+    fn deserialize_abmeldung() {
+        let result: Vec<Abmeldung> = serde_json::from_str(
+            r#"[
+                {"person_id": "550e8400-e29b-41d4-a716-446655440000"},
+                {
+                    "person_id": "550e8400-e29b-41d4-a716-446655440000",
+                    "von": "2022-05-27T19:00:00+02:00",
+                    "grund": "unbekannt"
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(result[0].grund, None);
+        assert!(!result[0].is_partial());
+        assert_eq!(result[1].grund, Some(AbmeldungGrund::Sonstiges));
+        assert_eq!(result[1].details(), Some("ab 19 Uhr".to_string()));
+    }
+
+    #[test]
+    fn mark_present() {
+        let rat = |id: &str, name: &str| PersonWithAbmeldung {
+            id: Uuid::parse_str(id).unwrap(),
+            name: name.to_string(),
+            abmeldung: None,
+            anwesend: false,
+        };
+
+        let mut räte = vec![
+            rat("550e8400-e29b-41d4-a716-446655440000", "Valentin Pukhov"),
+            rat("444e8400-e29b-41d4-a716-446655440000", "Florian Schubert"),
+            rat("333e8400-e29b-41d4-a716-446655440000", "Elif"),
+        ];
+
+        let entries = super::parse_present_list(
+            "# sign-in sheet\n\nvalentin pukhov\n  444e8400-e29b-41d4-a716-446655440000\nJonas\n",
+        );
+
+        let unknown = super::mark_present(&mut räte, &entries);
+
+        assert_eq!(unknown, vec!["Jonas"]);
+        assert!(Person {
+            id: Uuid::parse_str("333e8400-e29b-41d4-a716-446655440000").unwrap(),
+            name: "Elif".to_string(),
+        }
+        .matches(" elif"));
+        assert_eq!(
+            räte.iter().map(|r| r.anwesend).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+    }
+
+    #[test]
+    fn find_participant() {
+        let abmeldung = Abmeldung {
+            person_id: Uuid::nil(),
+            von: None,
+            bis: None,
+            grund: None,
+        };
+
+        let räte = vec![
+            PersonWithAbmeldung {
+                id: Uuid::new_v4(),
+                name: "Valentin".to_string(),
+                abmeldung: None,
+                anwesend: true,
+            },
+            PersonWithAbmeldung {
+                id: Uuid::new_v4(),
+                name: "Elif".to_string(),
+                abmeldung: Some(abmeldung),
+                anwesend: false,
+            },
+        ];
+
+        let gaeste = vec![Person {
+            id: Uuid::new_v4(),
+            name: "Lea".to_string(),
+        }];
+
+        let participants = super::possible_participants(&räte, &gaeste, true);
+
+        let valentin = super::find_participant(&participants, Some("valentin"), "redeleitung");
+        assert_eq!(valentin.unwrap().unwrap().name, "Valentin");
+
+        let lea = super::find_participant(&participants, Some("Lea"), "protokoll");
+        assert_eq!(lea.unwrap().unwrap().name, "Lea");
+
+        assert!(super::find_participant(&participants, Some("Elif"), "redeleitung").is_err());
+
+        // without a flag and without asking, the role stays empty
+        let nobody = super::find_participant(&participants, None, "redeleitung");
+        assert_eq!(nobody.unwrap(), None);
+
+        // without a known presence, only räte abgemeldet for the whole sitzung are left out
+        let unknown_presence = super::possible_participants(&räte, &[], false);
+        assert_eq!(
+            unknown_presence
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Valentin"]
+        );
+    }
 }
@@ -0,0 +1,284 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+
+use super::ProtokollFrontmatter;
+
+/// text fragments the protokoll template leaves behind, which have to be filled in
+/// before a protokoll can be published
+const PLACEHOLDERS: &[&str] = &[
+    "n Zustimmen",
+    "m Gegenstimmen",
+    "k Enthaltungen",
+    "um T Uhr",
+    "Wir sind mit n ",
+];
+
+/// list items of Top 0, which have to be filled in before a protokoll can be published
+const EMPTY_FIELDS: &[&str] = &[
+    "- Redeleitung:",
+    "- Protokoll:",
+    "- Startzeit:",
+    "- Endzeit:",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    pub line: usize,
+    pub text: String,
+}
+
+impl Display for Placeholder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("line {}: {}", self.line, self.text))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum FrontmatterKind {
+    Yaml,
+    Toml,
+}
+
+impl FrontmatterKind {
+    const fn delimiter(self) -> &'static str {
+        match self {
+            Self::Yaml => "---",
+            Self::Toml => "+++",
+        }
+    }
+
+    const fn separator(self) -> &'static str {
+        match self {
+            Self::Yaml => ":",
+            Self::Toml => "=",
+        }
+    }
+}
+
+/// finds all lines of the protokoll which still contain placeholders from the template
+pub fn find_placeholders(content: &str) -> Vec<Placeholder> {
+    let body_start = frontmatter_range(content).map_or(0, |(_, _, end)| end + 1);
+
+    content
+        .lines()
+        .enumerate()
+        .skip(body_start)
+        .filter(|(_, line)| {
+            PLACEHOLDERS.iter().any(|p| line.contains(p))
+                || EMPTY_FIELDS.iter().any(|f| line.trim() == *f)
+        })
+        .map(|(index, line)| Placeholder {
+            line: index + 1,
+            text: line.trim().to_string(),
+        })
+        .collect()
+}
+
+/// whether this protokoll is a draft, whose 'hiddenUntil' date has passed
+pub fn is_due(frontmatter: &ProtokollFrontmatter, today: NaiveDate) -> bool {
+    frontmatter.draft == Some(true) && frontmatter.hidden_until.is_some_and(|d| d <= today)
+}
+
+/// marks the protokoll as published, by setting 'draft' to false and updating 'lastmod'.
+/// the rest of the frontmatter is left untouched.
+pub fn publish(content: &str, today: NaiveDate) -> Result<String> {
+    let result = set_frontmatter_field(content, "draft", "false")?;
+    let lastmod = format!("\"{}\"", today.format("%Y-%m-%d"));
+
+    set_frontmatter_field(&result, "lastmod", &lastmod)
+}
+
+/// sets `key` to the already serialized `value` in the yaml or toml frontmatter of
+/// `content`. if the key doesnt exist yet, it is appended to the frontmatter.
+pub fn set_frontmatter_field(content: &str, key: &str, value: &str) -> Result<String> {
+    let Some((kind, start, end)) = frontmatter_range(content) else {
+        bail!("no frontmatter found");
+    };
+
+    let separator = kind.separator();
+    let replacement = match kind {
+        FrontmatterKind::Yaml => format!("{}{} {}", key, separator, value),
+        FrontmatterKind::Toml => format!("{} {} {}", key, separator, value),
+    };
+
+    // the lines keep their endings, so that the rest of the file stays untouched
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+
+    let existing = lines[start + 1..end].iter().position(|line| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with(separator))
+    });
+
+    // an inserted line takes the ending of the opening delimiter
+    let index = existing.map_or(start, |i| start + 1 + i);
+    let line_ending = if lines[index].ends_with("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let replacement_line = format!("{}{}", replacement, line_ending);
+
+    if existing.is_some() {
+        lines[index] = &replacement_line;
+    } else {
+        lines.insert(end, &replacement_line);
+    }
+
+    Ok(lines.concat())
+}
+
+/// returns the kind of the frontmatter as well as the line indices of its opening and
+/// closing delimiter
+fn frontmatter_range(content: &str) -> Option<(FrontmatterKind, usize, usize)> {
+    let mut lines = content.lines().enumerate();
+
+    let (start, kind) = lines
+        .by_ref()
+        .find(|(_, line)| !line.trim().is_empty())
+        .and_then(|(index, line)| match line.trim_end() {
+            "---" => Some((index, FrontmatterKind::Yaml)),
+            "+++" => Some((index, FrontmatterKind::Toml)),
+            _ => None,
+        })?;
+
+    let (end, _) = lines.find(|(_, line)| line.trim_end() == kind.delimiter())?;
+
+    Some((kind, start, end))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::Placeholder;
+
+    static PROTOKOLL_NO_TOPS: &str = include_str!("../../tests/protokoll-no-tops.md");
+
+    #[test]
+    fn find_placeholders() {
+        let result = super::find_placeholders(PROTOKOLL_NO_TOPS);
+
+        let expected = vec![
+            Placeholder {
+                line: 24,
+                text: "- Redeleitung:".to_string(),
+            },
+            Placeholder {
+                line: 25,
+                text: "- Protokoll:".to_string(),
+            },
+            Placeholder {
+                line: 26,
+                text: "- Startzeit:".to_string(),
+            },
+            Placeholder {
+                line: 27,
+                text: "- Endzeit:".to_string(),
+            },
+            Placeholder {
+                line: 28,
+                text: "- Wir sind mit n von 0 Rätys vielleicht beschlussfähig".to_string(),
+            },
+            Placeholder {
+                line: 44,
+                text: "_Top endet um T Uhr._".to_string(),
+            },
+            Placeholder {
+                line: 52,
+                text: "_Top endet um T Uhr._".to_string(),
+            },
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn publish_yaml() {
+        let protokoll = r#"---
+title: "Protokoll vom 27.05.2022"
+date: "2022-05-27"
+draft: true
+hiddenUntil: "2022-05-31"
+---
+
+## Top 0: Regularia
+"#;
+
+        let expected = r#"---
+title: "Protokoll vom 27.05.2022"
+date: "2022-05-27"
+draft: false
+hiddenUntil: "2022-05-31"
+lastmod: "2022-06-01"
+---
+
+## Top 0: Regularia
+"#;
+
+        let today = NaiveDate::from_ymd_opt(2022, 6, 1).unwrap();
+        let result = super::publish(protokoll, today).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn publish_toml() {
+        let protokoll = r#"+++
+title = "Protokoll vom 27.05.2022"
+draft    = true
+lastmod = "2022-05-27"
++++
+
+## Top 0: Regularia"#;
+
+        let expected = r#"+++
+title = "Protokoll vom 27.05.2022"
+draft = false
+lastmod = "2022-06-01"
++++
+
+## Top 0: Regularia"#;
+
+        let today = NaiveDate::from_ymd_opt(2022, 6, 1).unwrap();
+        let result = super::publish(protokoll, today).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn publish_crlf() {
+        let protokoll = "---\r\ndraft: true\r\n---\r\n\r\n## Top 0: Regularia\r\n";
+
+        let expected =
+            "---\r\ndraft: false\r\nlastmod: \"2022-06-01\"\r\n---\r\n\r\n## Top 0: Regularia\r\n";
+
+        let today = NaiveDate::from_ymd_opt(2022, 6, 1).unwrap();
+        let result = super::publish(protokoll, today).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn publish_without_frontmatter() {
+        let today = NaiveDate::from_ymd_opt(2022, 6, 1).unwrap();
+        let result = super::publish("## Top 0: Regularia", today);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn is_due() {
+        let protokoll = super::super::parse_protokoll(PROTOKOLL_NO_TOPS).unwrap();
+        let frontmatter = super::super::find_frontmatter(&protokoll).unwrap();
+
+        let before = NaiveDate::from_ymd_opt(2022, 5, 30).unwrap();
+        let after = NaiveDate::from_ymd_opt(2022, 5, 31).unwrap();
+
+        assert!(!super::is_due(&frontmatter, before));
+        assert!(super::is_due(&frontmatter, after));
+    }
+}
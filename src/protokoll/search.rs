@@ -0,0 +1,502 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use markdown::mdast;
+use serde::{Deserialize, Serialize};
+
+use super::parse::{parse_abstimmung, top_title};
+use super::{find_frontmatter, find_protokoll_date, parse_protokoll};
+use crate::SitzungTyp;
+
+/// bumped whenever the cached format changes, so that old caches are rebuilt
+const CACHE_VERSION: u32 = 2;
+
+/// how many characters are shown around the first match
+const SNIPPET_RADIUS: usize = 60;
+
+/// a top or an antrag within a top, which is searched as a whole
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Section {
+    pub top: String,
+    pub antrag: Option<String>,
+    /// the plain text of the section, with whitespace collapsed
+    pub text: String,
+    /// whether a vote within this section was accepted, not counting votes on the
+    /// dringlichkeit of an antrag
+    pub beschluss: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedProtokoll {
+    pub date: NaiveDate,
+    pub sitzung_kind: SitzungTyp,
+    pub sections: Vec<Section>,
+}
+
+/// splits a protokoll into its tops and anträge. everything before the first top, like
+/// the list of anwesende, isnt indexed.
+pub fn index_protokoll(content: &str) -> Result<IndexedProtokoll> {
+    let mdast = parse_protokoll(content)?;
+    let frontmatter = find_frontmatter(&mdast)?;
+    let date = find_protokoll_date(&frontmatter)?;
+
+    let mut sections: Vec<Section> = vec![];
+    // the section of the current top, to return to after an antrag
+    let mut top_index = 0;
+    let mut current = None;
+
+    for node in mdast.children().map_or(&[][..], |c| c.as_slice()) {
+        let text = plain_text(node);
+
+        match node {
+            mdast::Node::Heading(heading) if heading.depth == 2 => {
+                top_index = sections.len();
+                current = Some(top_index);
+                sections.push(Section {
+                    top: top_title(&text).to_string(),
+                    antrag: None,
+                    text: String::new(),
+                    beschluss: false,
+                });
+                continue;
+            }
+            mdast::Node::Heading(heading) if heading.depth > 2 && current.is_some() => {
+                if let Some(titel) = text.strip_prefix("Antrag:") {
+                    current = Some(sections.len());
+                    sections.push(Section {
+                        top: sections[top_index].top.clone(),
+                        antrag: Some(titel.trim().to_string()),
+                        text: String::new(),
+                        beschluss: false,
+                    });
+                    continue;
+                }
+
+                // any other heading ends the antrag before it
+                current = Some(top_index);
+            }
+            _ => (),
+        }
+
+        let Some(section) = current.and_then(|i| sections.get_mut(i)) else {
+            continue;
+        };
+
+        if let mdast::Node::Code(code) = node {
+            section.beschluss |= parse_abstimmung(code)
+                .is_some_and(|a| a.angenommen && a.has_counts() && !a.is_dringlichkeit());
+        }
+
+        if !text.is_empty() {
+            if !section.text.is_empty() {
+                section.text.push(' ');
+            }
+            section.text.push_str(&text);
+        }
+    }
+
+    Ok(IndexedProtokoll {
+        date,
+        sitzung_kind: frontmatter.sitzung_kind.unwrap_or(SitzungTyp::Normal),
+        sections,
+    })
+}
+
+/// the text of `node` and its children, separated and collapsed to single spaces
+fn plain_text(node: &mdast::Node) -> String {
+    let text = match node {
+        mdast::Node::Text(t) => t.value.clone(),
+        mdast::Node::InlineCode(c) => c.value.clone(),
+        mdast::Node::Code(c) => c.value.clone(),
+        mdast::Node::Html(_) | mdast::Node::Toml(_) | mdast::Node::Yaml(_) => String::new(),
+        _ => node
+            .children()
+            .map_or(&[][..], |c| c.as_slice())
+            .iter()
+            .map(plain_text)
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    /// all of these have to occur in a section, regardless of case
+    pub words: Vec<String>,
+    pub since: Option<NaiveDate>,
+    pub kind: Option<SitzungTyp>,
+    pub beschluss_only: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hit<'a> {
+    pub path: &'a Path,
+    pub date: NaiveDate,
+    pub sitzung_kind: &'a SitzungTyp,
+    pub section: &'a Section,
+    /// the text around the first match
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedProtokoll {
+    modified: SystemTime,
+    protokoll: IndexedProtokoll,
+}
+
+/// the indexed protokolle by their path, as stored on disk between searches
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchCache {
+    version: u32,
+    files: BTreeMap<PathBuf, CachedProtokoll>,
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+impl SearchCache {
+    /// loads the cache at `path`. a missing, unreadable or outdated cache is
+    /// rebuilt from scratch.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("unable to create cache directory")?;
+        }
+
+        let content = serde_json::to_string(self).context("unable to serialize cache")?;
+
+        fs::write(path, content)
+            .with_context(|| format!("unable to write cache '{}'", path.to_string_lossy()))
+    }
+
+    /// indexes all protokolle in `paths` which changed since they were cached and
+    /// forgets the ones which dont exist anymore. protokolle which cant be parsed are
+    /// returned with their error.
+    pub fn update(&mut self, paths: &[PathBuf]) -> Result<Vec<(PathBuf, anyhow::Error)>> {
+        self.files.retain(|path, _| path.exists());
+
+        let mut failed = vec![];
+
+        for path in paths {
+            let modified = fs::metadata(path)
+                .and_then(|m| m.modified())
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            if self.files.get(path).is_some_and(|c| c.modified == modified) {
+                continue;
+            }
+
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("unable to read '{}'", path.to_string_lossy()))?;
+
+            match index_protokoll(&content) {
+                Ok(protokoll) => {
+                    self.files.insert(
+                        path.clone(),
+                        CachedProtokoll {
+                            modified,
+                            protokoll,
+                        },
+                    );
+                }
+                Err(e) => {
+                    self.files.remove(path);
+                    failed.push((path.clone(), e));
+                }
+            }
+        }
+
+        Ok(failed)
+    }
+
+    /// finds all sections of protokolle below `root` matching `query`, the newest first
+    pub fn search(&self, root: &Path, query: &SearchQuery) -> Vec<Hit<'_>> {
+        let words: Vec<String> = query.words.iter().map(|w| w.to_lowercase()).collect();
+
+        let mut result: Vec<Hit> = self
+            .files
+            .iter()
+            .filter(|(path, _)| path.starts_with(root))
+            .map(|(path, cached)| (path, &cached.protokoll))
+            .filter(|(_, p)| query.since.is_none_or(|since| p.date >= since))
+            .filter(|(_, p)| query.kind.as_ref().is_none_or(|k| p.sitzung_kind == *k))
+            .flat_map(|(path, protokoll)| {
+                protokoll
+                    .sections
+                    .iter()
+                    .filter(|s| s.beschluss || !query.beschluss_only)
+                    .filter(|s| matches_all(s, &words))
+                    .map(|section| Hit {
+                        path,
+                        date: protokoll.date,
+                        sitzung_kind: &protokoll.sitzung_kind,
+                        section,
+                        snippet: snippet(&section.text, &words),
+                    })
+            })
+            .collect();
+
+        // sorting is stable, so the sections of a protokoll stay in order
+        result.sort_by_key(|h| Reverse(h.date));
+
+        result
+    }
+}
+
+fn matches_all(section: &Section, words: &[String]) -> bool {
+    let haystack = format!(
+        "{} {} {}",
+        section.top,
+        section.antrag.as_deref().unwrap_or_default(),
+        section.text
+    )
+    .to_lowercase();
+
+    words.iter().all(|w| haystack.contains(w.as_str()))
+}
+
+/// the text around the first of `words` occurring in `text`, or its beginning
+fn snippet(text: &str, words: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+
+    let matches_at = |start: usize, word: &str| {
+        chars[start..]
+            .iter()
+            .flat_map(|c| c.to_lowercase())
+            .take(word.chars().count())
+            .eq(word.chars())
+    };
+
+    let position = (0..chars.len())
+        .find(|i| words.iter().any(|w| matches_at(*i, w)))
+        .unwrap_or(0);
+
+    let start = position.saturating_sub(SNIPPET_RADIUS);
+    let end = (position + SNIPPET_RADIUS).min(chars.len());
+
+    let mut result: String = chars[start..end].iter().collect();
+
+    if start > 0 {
+        result.insert(0, '…');
+    }
+    if end < chars.len() {
+        result.push('…');
+    }
+
+    result
+}
+
+/// '$XDG_CACHE_HOME/prototool/search.json' or '~/.cache/prototool/search.json'
+pub fn default_cache_path() -> Option<PathBuf> {
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+
+    Some(cache_dir.join("prototool/search.json"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    use super::{SearchCache, SearchQuery, Section};
+    use crate::SitzungTyp;
+
+    static PROTOKOLL_WITH_TOPS: &str = include_str!("../../tests/protokoll-with-tops.md");
+    static PROTOKOLL_VV: &str = include_str!("../../tests/protokoll-vv.md");
+
+    /// the protokoll with tops, in which only the vote on the blumen was filled in
+    fn protokoll_with_beschluss() -> String {
+        PROTOKOLL_WITH_TOPS.replacen(
+            "n Zustimmen, m Gegenstimmen, k Enthaltungen",
+            "7 Zustimmen, 0 Gegenstimmen, 1 Enthaltungen",
+            1,
+        )
+    }
+
+    fn query(words: &[&str]) -> SearchQuery {
+        SearchQuery {
+            words: words.iter().map(|w| w.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn index_protokoll() {
+        let result = super::index_protokoll(&protokoll_with_beschluss()).unwrap();
+
+        let titles: Vec<(&str, Option<&str>, bool)> = result
+            .sections
+            .iter()
+            .map(|s| (s.top.as_str(), s.antrag.as_deref(), s.beschluss))
+            .collect();
+
+        assert_eq!(
+            titles,
+            vec![
+                ("Regularia", None, false),
+                ("Berichte, Mail und Post", None, false),
+                ("Blumen für Valentin", None, false),
+                ("Blumen für Valentin", Some("Blumen für Valentin"), true),
+                ("Volt Zapfanlage", None, false),
+                ("Volt Zapfanlage", Some("Tank für Voltzapfanlage"), false),
+                ("Volt Zapfanlage", Some("Hahn für Voltzapfanlage"), false),
+                ("Verschiedenes", None, false),
+            ]
+        );
+        assert_eq!(
+            result.sections[5],
+            Section {
+                top: "Volt Zapfanlage".to_string(),
+                antrag: Some("Tank für Voltzapfanlage".to_string()),
+                text: "Volt aus dem Hahn > Volt aus der Dose Die Fachschaft Informatik beschließt Abstimmung: n Zustimmen, m Gegenstimmen, k Enthaltungen".to_string(),
+                beschluss: false,
+            }
+        );
+    }
+
+    #[test]
+    fn snippet() {
+        let text = "a".repeat(100) + " Volt " + &"b".repeat(100);
+
+        let result = super::snippet(&text, &["volt".to_string()]);
+
+        assert_eq!(
+            result,
+            format!("…{} Volt {}…", "a".repeat(59), "b".repeat(55))
+        );
+        assert_eq!(super::snippet("Blumen", &["volt".to_string()]), "Blumen");
+    }
+
+    #[test]
+    fn search() {
+        let tmpdir = tempdir().unwrap();
+        let protokoll_dir = tmpdir.path().join("content/protokolle/2022");
+        let cache_path = tmpdir.path().join("cache/search.json");
+
+        fs::create_dir_all(&protokoll_dir).unwrap();
+
+        let paths = vec![
+            protokoll_dir.join("05-27-protokoll.de.md"),
+            protokoll_dir.join("05-27-vv-protokoll.de.md"),
+        ];
+
+        fs::write(&paths[0], protokoll_with_beschluss()).unwrap();
+        fs::write(&paths[1], PROTOKOLL_VV).unwrap();
+
+        let mut cache = SearchCache::default();
+        assert!(cache.update(&paths).unwrap().is_empty());
+        cache.save(&cache_path).unwrap();
+
+        let loaded = SearchCache::load(&cache_path);
+        let hits = loaded.search(tmpdir.path(), &query(&["VOLT", "hahn"]));
+
+        let found: Vec<(&str, Option<&str>)> = hits
+            .iter()
+            .map(|h| (h.section.top.as_str(), h.section.antrag.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                // the late antrag is mentioned in the regularia
+                ("Regularia", None),
+                ("Volt Zapfanlage", Some("Tank für Voltzapfanlage")),
+                ("Volt Zapfanlage", Some("Hahn für Voltzapfanlage")),
+            ]
+        );
+
+        let vv = loaded.search(
+            tmpdir.path(),
+            &SearchQuery {
+                kind: Some(SitzungTyp::VV),
+                ..query(&["Regularia"])
+            },
+        );
+        assert_eq!(vv.len(), 1);
+        assert_eq!(vv[0].path, paths[1]);
+
+        let later = SearchQuery {
+            since: NaiveDate::from_ymd_opt(2022, 5, 28),
+            ..query(&[])
+        };
+        assert!(loaded.search(tmpdir.path(), &later).is_empty());
+
+        let beschluesse = SearchQuery {
+            beschluss_only: true,
+            ..query(&["blumen"])
+        };
+        assert_eq!(loaded.search(tmpdir.path(), &beschluesse).len(), 1);
+
+        // the votes on the voltzapfanlage are still placeholders
+        let placeholders = SearchQuery {
+            beschluss_only: true,
+            ..query(&["voltzapfanlage"])
+        };
+        assert!(loaded.search(tmpdir.path(), &placeholders).is_empty());
+    }
+
+    #[test]
+    fn update_incrementally() {
+        let tmpdir = tempdir().unwrap();
+        let path = tmpdir.path().join("05-27-protokoll.de.md");
+
+        fs::write(&path, PROTOKOLL_WITH_TOPS).unwrap();
+
+        let mut cache = SearchCache::default();
+        cache.update(std::slice::from_ref(&path)).unwrap();
+
+        // changing the content without the mtime doesnt trigger reindexing
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, PROTOKOLL_VV).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        cache.update(std::slice::from_ref(&path)).unwrap();
+
+        assert_eq!(cache.search(tmpdir.path(), &query(&["volt"])).len(), 4);
+
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        cache.update(std::slice::from_ref(&path)).unwrap();
+
+        assert!(cache.search(tmpdir.path(), &query(&["volt"])).is_empty());
+
+        fs::remove_file(&path).unwrap();
+        cache.update(&[]).unwrap();
+
+        assert!(cache.files.is_empty());
+    }
+}
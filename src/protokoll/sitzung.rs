@@ -1,11 +1,18 @@
-use anyhow::{Context, Result};
-use chrono::{DateTime, FixedOffset};
-use reqwest::blocking::Client;
-use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Days, FixedOffset};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 use url::Url;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, PartialEq, Eq, strum::Display, Clone)]
+use super::{betrag::Betrag, person::Person};
+use crate::{api::ApiClient, timezone};
+
+#[derive(
+    Debug, Deserialize, Serialize, PartialEq, Eq, strum::Display, strum::EnumString, Clone,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum SitzungTyp {
     #[strum(to_string = "normal")]
@@ -22,13 +29,69 @@ pub enum SitzungTyp {
     Dringlichkeit,
 }
 
+impl SitzungTyp {
+    /// e.g. "Fachschaftsratssitzung" or "Vollversammlung"
+    pub const fn bezeichnung(&self) -> &'static str {
+        match self {
+            Self::Normal => "Fachschaftsratssitzung",
+            Self::VV => "Vollversammlung",
+            Self::WahlVV => "Wahlvollversammlung",
+            Self::Ersatz => "Ersatzsitzung",
+            Self::Konsti => "Konstituierende Sitzung",
+            Self::Dringlichkeit => "Dringlichkeitssitzung",
+        }
+    }
+
+    /// the bezeichnung after 'zur', e.g. "Einladung zur konstituierenden Sitzung"
+    pub const fn bezeichnung_dativ(&self) -> &'static str {
+        match self {
+            Self::Konsti => "konstituierenden Sitzung",
+            _ => self.bezeichnung(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Sitzung {
     pub id: Uuid,
     pub datetime: DateTime<FixedOffset>,
     pub typ: SitzungTyp,
     pub tops: Vec<Top>,
-    pub antragsfrist: DateTime<FixedOffset>,
+    /// older versions of the api dont provide an antragsfrist
+    #[serde(default)]
+    pub antragsfrist: Option<DateTime<FixedOffset>>,
+}
+
+impl Sitzung {
+    /// converts all datetimes of this sitzung and its anträge to `tz`
+    pub fn to_timezone(&mut self, tz: &Tz) {
+        self.datetime = timezone::convert(self.datetime, tz);
+        self.antragsfrist = self.antragsfrist.map(|a| timezone::convert(a, tz));
+
+        for antrag in self.tops.iter_mut().flat_map(|t| t.antraege.iter_mut()) {
+            antrag.erstellt_am = timezone::convert(antrag.erstellt_am, tz);
+        }
+    }
+
+    /// sets the antragsfrist to `days` before the sitzung, if the api didnt provide one.
+    /// the days are counted in `tz`, so the antragsfrist keeps the time of the sitzung
+    /// across dst changes.
+    pub fn fill_antragsfrist(&mut self, days: u64, tz: &Tz) {
+        if self.antragsfrist.is_none() {
+            self.antragsfrist = self
+                .datetime
+                .with_timezone(tz)
+                .checked_sub_days(Days::new(days))
+                .map(|a| a.fixed_offset());
+        }
+    }
+
+    /// whether `antrag` was submitted after the antragsfrist. without an antragsfrist
+    /// every antrag counts as submitted on time.
+    pub fn is_late(&self, antrag: &Antrag) -> bool {
+        self.antragsfrist
+            .is_some_and(|antragsfrist| antrag.erstellt_am > antragsfrist)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -37,6 +100,29 @@ pub struct Antrag {
     pub antragstext: String,
     pub begruendung: String,
     pub erstellt_am: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub antragsteller: Vec<Person>,
+    /// the requested amount of money, if this is a finanzantrag
+    #[serde(default)]
+    pub betrag: Option<Betrag>,
+    #[serde(default)]
+    pub haushaltstitel: Option<String>,
+    #[serde(default)]
+    pub anhaenge: Vec<Anhang>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+pub struct Anhang {
+    pub name: Option<String>,
+    pub url: Url,
+}
+
+impl Display for Anhang {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = self.name.as_deref().unwrap_or(self.url.as_str());
+
+        f.write_fmt(format_args!("[{}]({})", name, self.url))
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
@@ -57,34 +143,95 @@ pub struct Top {
     pub inhalt: String,
 }
 
-pub fn fetch_sitzung(
-    api_url: &Url,
-    client: &Client,
+impl Display for Top {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}", self.name.as_str()))
+    }
+}
+
+pub fn fetch_sitzung(api: &ApiClient, datetime: DateTime<FixedOffset>) -> Result<Sitzung> {
+    let sitzungen = fetch_sitzungen(api, datetime, 1)?;
+
+    sitzungen
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("there is no upcoming sitzung"))
+}
+
+/// fetches the next `limit` sitzungen after `datetime`
+pub fn fetch_sitzungen(
+    api: &ApiClient,
     datetime: DateTime<FixedOffset>,
-) -> Result<Sitzung> {
-    let mut endpoint = api_url.join("api/sitzungen/after/")?;
-    endpoint.set_query(Some(
-        format!(
-            "timestamp={}&limit=1",
-            datetime
-                .to_rfc3339()
-                .replace(":", "%3A")
-                .replace("+", "%2B")
-        )
-        .as_str(),
-    ));
-
-    let response = client
-        .get(endpoint)
-        .send()
-        .context("unable to fetch next sitzung")?
-        .error_for_status()
-        .context("unable to fetch next sitzung")?;
-
-    let sitzungen = response
-        .json::<Vec<Sitzung>>()
-        .context("failed to deserialize sitzung")?;
-    let sitzung = sitzungen[0].clone();
-
-    Ok(sitzung)
+    limit: usize,
+) -> Result<Vec<Sitzung>> {
+    let timestamp = datetime.to_rfc3339();
+
+    api.get_json(
+        "api/sitzungen/after/",
+        &[("timestamp", &timestamp), ("limit", &limit.to_string())],
+    )
+    .context("unable to fetch next sitzung")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::{Antrag, Sitzung, SitzungTyp, Top, TopTyp};
+    use crate::timezone::DEFAULT_TIMEZONE;
+
+    fn sitzung(rfc3339: &str) -> Sitzung {
+        let datetime = DateTime::parse_from_rfc3339(rfc3339).unwrap();
+
+        Sitzung {
+            id: Uuid::nil(),
+            datetime,
+            typ: SitzungTyp::Normal,
+            tops: vec![Top {
+                name: "Volt".to_string(),
+                weight: 1,
+                typ: TopTyp::Normal,
+                inhalt: String::new(),
+                antraege: vec![Antrag {
+                    titel: "Tank".to_string(),
+                    antragstext: String::new(),
+                    begruendung: String::new(),
+                    erstellt_am: datetime,
+                    antragsteller: vec![],
+                    betrag: None,
+                    haushaltstitel: None,
+                    anhaenge: vec![],
+                }],
+            }],
+            antragsfrist: None,
+        }
+    }
+
+    #[test]
+    fn to_timezone() {
+        let mut result = sitzung("2022-05-27T16:30:00Z");
+
+        result.to_timezone(&DEFAULT_TIMEZONE);
+
+        assert_eq!(result.datetime.to_rfc3339(), "2022-05-27T18:30:00+02:00");
+        assert_eq!(
+            result.tops[0].antraege[0].erstellt_am.to_rfc3339(),
+            "2022-05-27T18:30:00+02:00"
+        );
+    }
+
+    #[test]
+    fn fill_antragsfrist_across_dst() {
+        let mut result = sitzung("2022-03-28T18:30:00+02:00");
+
+        result.fill_antragsfrist(3, &DEFAULT_TIMEZONE);
+
+        assert_eq!(
+            result.antragsfrist.unwrap().to_rfc3339(),
+            "2022-03-25T18:30:00+01:00"
+        );
+    }
 }
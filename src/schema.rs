@@ -0,0 +1,293 @@
+//! the shapes of the api responses prototool expects, to report precisely which field
+//! of a response doesnt match instead of failing to deserialize it as a whole
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, NaiveDate};
+use serde_json::Value;
+use url::Url;
+use uuid::Uuid;
+
+use crate::Betrag;
+
+/// the expected shape of a json value. these mirror the deserialize implementations
+/// of `Sitzung`, `Person`, `Abmeldung` and `Event` and have to be kept in sync.
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    String,
+    Integer,
+    Uuid,
+    /// a datetime in rfc 3339 format
+    DateTime,
+    /// a datetime or a date
+    EventTime,
+    Url,
+    /// a number in euro or a string like "12,50 €"
+    Betrag,
+    /// one of the given strings
+    Enum(&'static [&'static str]),
+    /// may be missing or null
+    Optional(&'static Self),
+    /// may be missing, but not null
+    Defaulted(&'static Self),
+    List(&'static Self),
+    Object(&'static [(&'static str, Self)]),
+}
+
+pub const PERSON: Shape = Shape::Object(&[("id", Shape::Uuid), ("name", Shape::String)]);
+
+pub const ANHANG: Shape = Shape::Object(&[
+    ("name", Shape::Optional(&Shape::String)),
+    ("url", Shape::Url),
+]);
+
+pub const ANTRAG: Shape = Shape::Object(&[
+    ("titel", Shape::String),
+    ("antragstext", Shape::String),
+    ("begruendung", Shape::String),
+    ("erstellt_am", Shape::DateTime),
+    ("antragsteller", Shape::Defaulted(&Shape::List(&PERSON))),
+    ("betrag", Shape::Optional(&Shape::Betrag)),
+    ("haushaltstitel", Shape::Optional(&Shape::String)),
+    ("anhaenge", Shape::Defaulted(&Shape::List(&ANHANG))),
+]);
+
+pub const TOP: Shape = Shape::Object(&[
+    ("weight", Shape::Integer),
+    ("name", Shape::String),
+    ("antraege", Shape::List(&ANTRAG)),
+    (
+        "typ",
+        Shape::Enum(&["regularia", "bericht", "normal", "verschiedenes"]),
+    ),
+    ("inhalt", Shape::String),
+]);
+
+pub const SITZUNG: Shape = Shape::Object(&[
+    ("id", Shape::Uuid),
+    ("datetime", Shape::DateTime),
+    (
+        "typ",
+        Shape::Enum(&[
+            "normal",
+            "vv",
+            "wahlvv",
+            "ersatz",
+            "konsti",
+            "dringlichkeit",
+        ]),
+    ),
+    ("tops", Shape::List(&TOP)),
+    ("antragsfrist", Shape::Optional(&Shape::DateTime)),
+]);
+
+pub const ABMELDUNG: Shape = Shape::Object(&[
+    ("person_id", Shape::Uuid),
+    ("von", Shape::Optional(&Shape::DateTime)),
+    ("bis", Shape::Optional(&Shape::DateTime)),
+    // unknown reasons are shown as 'Sonstiges'
+    ("grund", Shape::Optional(&Shape::String)),
+]);
+
+pub const EVENT: Shape = Shape::Object(&[
+    ("summary", Shape::Optional(&Shape::String)),
+    ("location", Shape::Optional(&Shape::String)),
+    ("start", Shape::EventTime),
+    ("end", Shape::Optional(&Shape::EventTime)),
+]);
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => f.write_str("string"),
+            Self::Integer => f.write_str("integer"),
+            Self::Uuid => f.write_str("uuid"),
+            Self::DateTime => f.write_str("datetime (rfc 3339)"),
+            Self::EventTime => f.write_str("datetime (rfc 3339) or date"),
+            Self::Url => f.write_str("url"),
+            Self::Betrag => f.write_str("betrag (number or string)"),
+            Self::Enum(variants) => write!(f, "one of {}", variants.join(", ")),
+            Self::Optional(shape) => write!(f, "{} or null", shape),
+            Self::Defaulted(shape) => write!(f, "{}", shape),
+            Self::List(shape) => write!(f, "list of {}", shape),
+            Self::Object(_) => f.write_str("object"),
+        }
+    }
+}
+
+/// a field, which doesnt match its shape
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// e.g. '[0].tops[1].antraege[0].erstellt_am'
+    pub path: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.path, self.expected, self.found
+        )
+    }
+}
+
+/// checks `value` against `shape` and returns all mismatching fields
+pub fn check(value: &Value, shape: &Shape) -> Vec<Mismatch> {
+    let mut result = vec![];
+
+    check_value(value, shape, "", &mut result);
+
+    result
+}
+
+fn check_value(value: &Value, shape: &Shape, path: &str, result: &mut Vec<Mismatch>) {
+    let matches = match (shape, value) {
+        (Shape::Optional(_), Value::Null) => true,
+        (Shape::Optional(inner) | Shape::Defaulted(inner), _) => {
+            return check_value(value, inner, path, result);
+        }
+        (Shape::String, Value::String(_)) => true,
+        (Shape::Integer, Value::Number(n)) => n.is_i64(),
+        (Shape::Uuid, Value::String(s)) => Uuid::parse_str(s).is_ok(),
+        (Shape::DateTime, Value::String(s)) => DateTime::parse_from_rfc3339(s).is_ok(),
+        (Shape::EventTime, Value::String(s)) => {
+            DateTime::parse_from_rfc3339(s).is_ok() || s.parse::<NaiveDate>().is_ok()
+        }
+        (Shape::Url, Value::String(s)) => Url::parse(s).is_ok(),
+        (Shape::Betrag, Value::Number(_)) => true,
+        (Shape::Betrag, Value::String(s)) => s.parse::<Betrag>().is_ok(),
+        (Shape::Enum(variants), Value::String(s)) => variants.contains(&s.as_str()),
+        (Shape::List(inner), Value::Array(values)) => {
+            for (index, element) in values.iter().enumerate() {
+                check_value(element, inner, &format!("{}[{}]", path, index), result);
+            }
+
+            true
+        }
+        (Shape::Object(fields), Value::Object(map)) => {
+            for (name, field_shape) in *fields {
+                let field_path = if path.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+
+                match map.get(*name) {
+                    Some(field) => check_value(field, field_shape, &field_path, result),
+                    None if matches!(field_shape, Shape::Optional(_) | Shape::Defaulted(_)) => (),
+                    None => result.push(Mismatch {
+                        path: field_path,
+                        expected: field_shape.to_string(),
+                        found: "nothing".to_string(),
+                    }),
+                }
+            }
+
+            true
+        }
+        _ => false,
+    };
+
+    if !matches {
+        result.push(Mismatch {
+            path: if path.is_empty() {
+                ".".to_string()
+            } else {
+                path.to_string()
+            },
+            expected: shape.to_string(),
+            found: describe(value),
+        });
+    }
+}
+
+/// e.g. "string 'morgen'" or "number 3"
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("bool {}", b),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(s) => format!("string '{}'", s),
+        Value::Array(_) => "list".to_string(),
+        Value::Object(_) => "object".to_string(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::{Shape, ABMELDUNG, EVENT, PERSON, SITZUNG};
+
+    static SITZUNGEN: &str = include_str!("../tests/mock/sitzungen.json");
+    static PERSONS: &str = include_str!("../tests/mock/persons.json");
+    static ABMELDUNGEN: &str = include_str!("../tests/mock/abmeldungen.json");
+    static EVENTS: &str = include_str!("../tests/mock/events.json");
+
+    fn mismatches(value: serde_json::Value, shape: &Shape) -> Vec<String> {
+        super::check(&value, shape)
+            .iter()
+            .map(|m| m.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fixtures_match() {
+        let check = |content: &str, shape: &'static Shape| {
+            super::check(&serde_json::from_str(content).unwrap(), &Shape::List(shape))
+        };
+
+        assert_eq!(check(SITZUNGEN, &SITZUNG), vec![]);
+        assert_eq!(check(PERSONS, &PERSON), vec![]);
+        assert_eq!(check(ABMELDUNGEN, &ABMELDUNG), vec![]);
+        assert_eq!(check(EVENTS, &EVENT), vec![]);
+    }
+
+    #[test]
+    fn renamed_field() {
+        let sitzungen = json!([{
+            "id": "5d1cbc5e-8a1f-4e0a-9d39-2f4b0f6a3c01",
+            "date": "2022-05-27T18:30:00+02:00",
+            "typ": "normal",
+            "tops": [{
+                "weight": 1.5,
+                "name": "Volt Zapfanlage",
+                "typ": "wichtig",
+                "inhalt": "",
+                "antraege": [{
+                    "titel": "Neue Zapfanlage",
+                    "antragstext": "",
+                    "begruendung": "",
+                    "erstellt_am": "gestern",
+                    "antragsteller": null,
+                    "betrag": "viel"
+                }]
+            }]
+        }]);
+
+        assert_eq!(
+            mismatches(sitzungen, &Shape::List(&SITZUNG)),
+            vec![
+                "[0].datetime: expected datetime (rfc 3339), found nothing",
+                "[0].tops[0].weight: expected integer, found number 1.5",
+                "[0].tops[0].antraege[0].erstellt_am: expected datetime (rfc 3339), found string 'gestern'",
+                "[0].tops[0].antraege[0].antragsteller: expected list of object, found null",
+                "[0].tops[0].antraege[0].betrag: expected betrag (number or string), found string 'viel'",
+                "[0].tops[0].typ: expected one of regularia, bericht, normal, verschiedenes, found string 'wichtig'",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_root() {
+        assert_eq!(
+            mismatches(json!({"persons": []}), &Shape::List(&PERSON)),
+            vec![".: expected list of object, found object"]
+        );
+    }
+}
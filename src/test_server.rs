@@ -0,0 +1,103 @@
+//! a minimal http server to test api clients against
+
+#![allow(clippy::unwrap_used)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+use url::Url;
+
+/// a request as received by the stub server
+#[derive(Debug)]
+pub struct StubRequest {
+    pub request_line: String,
+    /// header names are lowercase
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// answers a single request with `status` and returns the request line and body
+pub fn stub_server(status: &'static str) -> (Url, JoinHandle<(String, String)>) {
+    let (url, requests) = stub_responses(&[(status, "")]);
+
+    let handle = thread::spawn(move || {
+        let request = requests.join().unwrap().remove(0);
+
+        (request.request_line, request.body)
+    });
+
+    (url, handle)
+}
+
+/// answers one request per entry of `responses` with its status and body, in order, and
+/// returns the received requests
+pub fn stub_responses(
+    responses: &[(&'static str, &'static str)],
+) -> (Url, JoinHandle<Vec<StubRequest>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let owned_responses = responses.to_vec();
+
+    let handle = thread::spawn(move || {
+        let mut requests = vec![];
+
+        for (status, response_body) in owned_responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut headers = vec![];
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+
+                if header.trim().is_empty() {
+                    break;
+                }
+
+                if let Some((name, value)) = header.split_once(':') {
+                    headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+                }
+            }
+
+            let content_length = headers
+                .iter()
+                .find(|(name, _)| name == "content-length")
+                .map_or(0, |(_, value)| value.parse().unwrap());
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                response_body.len(),
+                response_body
+            )
+            .unwrap();
+
+            requests.push(StubRequest {
+                request_line: request_line.trim().to_string(),
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+        }
+
+        requests
+    });
+
+    (url, handle)
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// the timezone sitzungen take place in, unless configured otherwise
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Berlin;
+
+/// the current time in `tz`
+pub fn now(tz: &Tz) -> DateTime<FixedOffset> {
+    Utc::now().with_timezone(tz).fixed_offset()
+}
+
+/// the current date in `tz`, regardless of the timezone of the machine
+pub fn today(tz: &Tz) -> NaiveDate {
+    Utc::now().with_timezone(tz).date_naive()
+}
+
+/// the first moment of `date` in `tz`. if midnight is skipped by a dst change, the day
+/// starts at the first moment after it.
+pub fn start_of_day(tz: &Tz, date: NaiveDate) -> DateTime<FixedOffset> {
+    let midnight = date.and_time(NaiveTime::MIN);
+
+    (0..24)
+        .find_map(|hours| {
+            tz.from_local_datetime(&(midnight + TimeDelta::hours(hours)))
+                .earliest()
+        })
+        .unwrap_or_else(|| tz.from_utc_datetime(&midnight))
+        .fixed_offset()
+}
+
+/// `datetime` as seen in `tz`. the api might answer in any offset, so every datetime
+/// is converted before its date or time is shown.
+pub fn convert(datetime: DateTime<FixedOffset>, tz: &Tz) -> DateTime<FixedOffset> {
+    datetime.with_timezone(tz).fixed_offset()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::{DateTime, NaiveDate};
+    use pretty_assertions::assert_eq;
+
+    use super::DEFAULT_TIMEZONE;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn start_of_day() {
+        let start = |d| super::start_of_day(&DEFAULT_TIMEZONE, d).to_rfc3339();
+
+        // dst starts at 02:00 on 2022-03-27 and ends at 03:00 on 2022-10-30
+        assert_eq!(start(date(2022, 3, 27)), "2022-03-27T00:00:00+01:00");
+        assert_eq!(start(date(2022, 3, 28)), "2022-03-28T00:00:00+02:00");
+        assert_eq!(start(date(2022, 10, 30)), "2022-10-30T00:00:00+02:00");
+        assert_eq!(start(date(2022, 10, 31)), "2022-10-31T00:00:00+01:00");
+    }
+
+    #[test]
+    fn start_of_day_without_midnight() {
+        // in são paulo, dst started at midnight on 2018-11-04
+        let start = super::start_of_day(&chrono_tz::America::Sao_Paulo, date(2018, 11, 4));
+
+        assert_eq!(start.to_rfc3339(), "2018-11-04T01:00:00-02:00");
+    }
+
+    #[test]
+    fn convert() {
+        let datetime = DateTime::parse_from_rfc3339("2022-05-27T22:30:00Z").unwrap();
+
+        let result = super::convert(datetime, &DEFAULT_TIMEZONE);
+
+        assert_eq!(result.to_rfc3339(), "2022-05-28T00:30:00+02:00");
+        assert_eq!(result.date_naive(), date(2022, 5, 28));
+    }
+}
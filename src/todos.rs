@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use askama::Template;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{protokoll::parse::ParsedProtokoll, Person};
+
+/// a todo, which wasnt checked in the latest protokoll it appears in
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct OpenTodo {
+    /// the markdown of the item, e.g. '@Valentin kümmert sich um Blumen'
+    pub text: String,
+    /// the date of the sitzung in which the todo came up first
+    pub since: NaiveDate,
+    /// the date of the latest protokoll listing the todo
+    pub last_seen: NaiveDate,
+    /// the days passed since the todo came up
+    pub age: i64,
+}
+
+/// the open todos of a person
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct TodoGroup {
+    /// the name of the rat, the mention as written if it matches no rat, or `None`
+    /// for todos without a mention
+    pub assignee: Option<String>,
+    /// whether the assignee is a rat
+    pub is_rat: bool,
+    pub todos: Vec<OpenTodo>,
+}
+
+/// collects the todos of all `protokolle`, which are still open in the latest
+/// protokoll they appear in. todos are recognized by their text, so a carried over
+/// todo is only listed once.
+pub fn open_todos(protokolle: &[ParsedProtokoll], today: NaiveDate) -> Vec<OpenTodo> {
+    let mut sorted: Vec<&ParsedProtokoll> = protokolle.iter().collect();
+    sorted.sort_by_key(|p| p.date);
+
+    let mut todos: BTreeMap<&str, (OpenTodo, bool)> = BTreeMap::new();
+
+    for protokoll in sorted {
+        for todo in &protokoll.todos {
+            let (entry, done) = todos.entry(&todo.text).or_insert_with(|| {
+                let open = OpenTodo {
+                    text: todo.text.clone(),
+                    since: todo.since,
+                    last_seen: protokoll.date,
+                    age: 0,
+                };
+
+                (open, todo.done)
+            });
+
+            entry.since = entry.since.min(todo.since);
+            entry.last_seen = protokoll.date;
+            *done = todo.done;
+        }
+    }
+
+    let mut result: Vec<OpenTodo> = todos
+        .into_values()
+        .filter(|(_, done)| !done)
+        .map(|(todo, _)| OpenTodo {
+            age: (today - todo.since).num_days(),
+            ..todo
+        })
+        .collect();
+
+    result.sort_by_key(|t| t.since);
+
+    result
+}
+
+/// the names mentioned in `text`, e.g. 'Valentin' for '@Valentin kümmert sich'
+pub fn mentions(text: &str) -> Vec<&str> {
+    let mut result = vec![];
+
+    for (index, _) in text.match_indices('@') {
+        // mail addresses arent mentions
+        if text[..index]
+            .chars()
+            .last()
+            .is_some_and(|c| !c.is_whitespace())
+        {
+            continue;
+        }
+
+        let rest = &text[index + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+
+        if end > 0 {
+            result.push(&rest[..end]);
+        }
+    }
+
+    result
+}
+
+/// finds the rat meant by `mention`, either by its full name or, if unambiguous, by
+/// the first word of its name
+fn find_rat<'a>(raete: &'a [Person], mention: &str) -> Option<&'a Person> {
+    if let Some(rat) = raete.iter().find(|r| r.matches(mention)) {
+        return Some(rat);
+    }
+
+    let mut by_first_name = raete.iter().filter(|r| {
+        r.name
+            .split_whitespace()
+            .next()
+            .is_some_and(|n| n.eq_ignore_ascii_case(mention))
+    });
+
+    match (by_first_name.next(), by_first_name.next()) {
+        (Some(rat), None) => Some(rat),
+        _ => None,
+    }
+}
+
+/// who a todo is assigned to. the order of the variants is the order of the groups.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Assignee {
+    Rat(String),
+    /// a mention, which matches no rat
+    Other(String),
+    Nobody,
+}
+
+/// groups `todos` by the persons mentioned in them. todos mentioning several persons
+/// are listed for each of them. räte come first, ordered by name, followed by other
+/// mentions and the todos without any.
+pub fn group_by_assignee(todos: &[OpenTodo], raete: &[Person]) -> Vec<TodoGroup> {
+    let mut groups: BTreeMap<Assignee, Vec<OpenTodo>> = BTreeMap::new();
+
+    for todo in todos {
+        let mut assignees: Vec<Assignee> = mentions(&todo.text)
+            .into_iter()
+            .map(|mention| {
+                find_rat(raete, mention).map_or_else(
+                    || Assignee::Other(mention.to_string()),
+                    |rat| Assignee::Rat(rat.name.clone()),
+                )
+            })
+            .collect();
+
+        if assignees.is_empty() {
+            assignees.push(Assignee::Nobody);
+        }
+
+        assignees.sort();
+        assignees.dedup();
+
+        for assignee in assignees {
+            groups.entry(assignee).or_default().push(todo.clone());
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(assignee, group_todos)| {
+            let (name, is_rat) = match assignee {
+                Assignee::Rat(name) => (Some(name), true),
+                Assignee::Other(name) => (Some(name), false),
+                Assignee::Nobody => (None, false),
+            };
+
+            TodoGroup {
+                assignee: name,
+                is_rat,
+                todos: group_todos,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Template)]
+#[template(path = "../templates/todos.md")]
+pub struct TodosTemplate<'a> {
+    pub date: NaiveDate,
+    pub groups: &'a [TodoGroup],
+}
+
+/// renders a page listing the open todos of each assignee
+pub fn render_page(groups: &[TodoGroup], date: NaiveDate) -> Result<String> {
+    let template = TodosTemplate { date, groups };
+
+    template.render().context("failed to render todos template")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::{OpenTodo, TodoGroup};
+    use crate::{
+        protokoll::parse::{ParsedProtokoll, Todo},
+        Person, SitzungTyp,
+    };
+
+    static TODOS: &str = include_str!("../tests/todos.md");
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 5, day).unwrap()
+    }
+
+    fn protokoll(day: u32, todos: &[(&str, u32, bool)]) -> ParsedProtokoll {
+        ParsedProtokoll {
+            date: date(day),
+            sitzung_kind: SitzungTyp::Normal,
+            draft: false,
+            anwesende: vec![],
+            tops: vec![],
+            todos: todos
+                .iter()
+                .map(|(text, since, done)| Todo {
+                    text: text.to_string(),
+                    since: date(*since),
+                    done: *done,
+                })
+                .collect(),
+        }
+    }
+
+    fn open(text: &str, since: u32, last_seen: u32) -> OpenTodo {
+        OpenTodo {
+            text: text.to_string(),
+            since: date(since),
+            last_seen: date(last_seen),
+            age: (date(31) - date(since)).num_days(),
+        }
+    }
+
+    fn person(name: &str) -> Person {
+        Person {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn open_todos() {
+        let protokolle = vec![
+            protokoll(
+                20,
+                &[
+                    ("@Elif Zapfanlage bestellen", 20, false),
+                    ("Raum buchen", 20, false),
+                ],
+            ),
+            protokoll(
+                13,
+                &[
+                    ("@Valentin Blumen kaufen", 13, false),
+                    ("@Elif Zapfanlage bestellen", 13, false),
+                ],
+            ),
+            protokoll(
+                27,
+                &[
+                    ("@Valentin Blumen kaufen", 13, false),
+                    ("@Elif Zapfanlage bestellen", 13, true),
+                ],
+            ),
+        ];
+
+        let result = super::open_todos(&protokolle, date(31));
+
+        assert_eq!(
+            result,
+            vec![
+                open("@Valentin Blumen kaufen", 13, 27),
+                open("Raum buchen", 20, 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn mentions() {
+        assert_eq!(
+            super::mentions("@Valentin und @Jonas-Peter, fragt fscs@hhu.de"),
+            vec!["Valentin", "Jonas-Peter"]
+        );
+        assert!(super::mentions("Raum buchen").is_empty());
+    }
+
+    #[test]
+    fn group_by_assignee() {
+        let raete = vec![
+            person("Valentin"),
+            person("Jonas \"Kooptimus\""),
+            person("Elif"),
+        ];
+
+        let todos = vec![
+            open("@valentin Blumen kaufen", 13, 27),
+            open("@Jonas und @Valentin Zapfanlage bestellen", 20, 27),
+            open("@Lea Raum buchen", 20, 27),
+            open("Schlüssel abholen", 27, 27),
+        ];
+
+        let result = super::group_by_assignee(&todos, &raete);
+
+        let group = |assignee: Option<&str>, is_rat, indices: &[usize]| TodoGroup {
+            assignee: assignee.map(str::to_string),
+            is_rat,
+            todos: indices.iter().map(|i| todos[*i].clone()).collect(),
+        };
+
+        assert_eq!(
+            result,
+            vec![
+                group(Some("Jonas \"Kooptimus\""), true, &[1]),
+                group(Some("Valentin"), true, &[0, 1]),
+                group(Some("Lea"), false, &[2]),
+                group(None, false, &[3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_page() {
+        let groups = vec![
+            TodoGroup {
+                assignee: Some("Valentin".to_string()),
+                is_rat: true,
+                todos: vec![open("@Valentin Blumen kaufen", 13, 27)],
+            },
+            TodoGroup {
+                assignee: None,
+                is_rat: false,
+                todos: vec![open("Schlüssel abholen", 27, 27)],
+            },
+        ];
+
+        let result = super::render_page(&groups, date(31)).unwrap();
+
+        assert_eq!(result, TODOS);
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde_json::json;
+use url::Url;
+
+/// posts `message` to a chat webhook. discord expects the message in 'content', while
+/// slack and matrix bridges expect it in 'text', so both are set.
+pub fn post_message(client: &Client, webhook_url: &Url, message: &str) -> Result<()> {
+    client
+        .post(webhook_url.clone())
+        .json(&json!({ "content": message, "text": message }))
+        .send()
+        .context("unable to post to webhook")?
+        .error_for_status()
+        .context("unable to post to webhook")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use reqwest::blocking::Client;
+    use serde_json::json;
+
+    use crate::test_server::stub_server;
+
+    #[test]
+    fn post_message() {
+        let (url, handle) = stub_server("204 No Content");
+        let webhook_url = url.join("webhooks/123").unwrap();
+
+        super::post_message(&Client::new(), &webhook_url, "Das Pad ist bereit").unwrap();
+
+        let (request_line, body) = handle.join().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(request_line, "POST /webhooks/123 HTTP/1.1");
+        assert_eq!(
+            json,
+            json!({ "content": "Das Pad ist bereit", "text": "Das Pad ist bereit" })
+        );
+    }
+
+    #[test]
+    fn post_message_fails() {
+        let (url, handle) = stub_server("404 Not Found");
+
+        let result = super::post_message(&Client::new(), &url, "Das Pad ist bereit");
+
+        handle.join().unwrap();
+
+        assert!(result.is_err());
+    }
+}